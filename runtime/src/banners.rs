@@ -1,11 +1,50 @@
-use support::{decl_storage, decl_module, StorageValue, StorageMap,
-    dispatch::Result, ensure, decl_event, traits::Currency};
-use system::ensure_signed;
-use runtime_primitives::traits::{As, Hash};
-use parity_codec::{Encode, Decode};
+use support::{decl_storage, decl_module, StorageValue, StorageMap, StorageDoubleMap,
+    dispatch::Result, ensure, decl_event, traits::{Currency, ReservableCurrency}};
+use system::{ensure_signed, ensure_root};
+use runtime_primitives::traits::{As, CheckedAdd, CheckedMul, CheckedSub, Hash, One, Saturating, Zero};
+use parity_codec::{Encode, Decode, Codec};
 use rstd::prelude::Vec;
+use client::decl_runtime_apis;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 
 const AUCTION_DURATION: u64 = 24*600;
+/// Upper bound on the number of entries returned by `auctions_ending_for`.
+const MAX_ENDING_AUCTIONS: usize = 100;
+/// Most entries `active_auctions_detailed` returns per page.
+const MAX_AUCTION_PAGE: u64 = 100;
+/// Most ids `banners_by_ids` looks up in a single call.
+const MAX_BANNERS_BY_IDS: usize = 100;
+/// Most ids `banners_page` and `banners_of` return per page.
+const MAX_BANNER_PAGE: u64 = 100;
+/// Most banners `migrate_account` moves in a single call.
+const MAX_MIGRATE_BATCH: u64 = 50;
+/// Most auctions `settle_expired` will look at in a single call.
+const MAX_SETTLE_BATCH: usize = 50;
+/// Most of the caller's banners `cancel_all_auctions` looks at in a single call.
+const MAX_CANCEL_BATCH: u64 = 50;
+/// Most entries `top_banners_by_bids` returns.
+const MAX_TOP_BANNERS: usize = 20;
+/// Most live auctions a single account may be the high bidder on at once.
+const MAX_LEADING_BIDS: usize = 32;
+/// Longest invite list a private auction may have.
+const MAX_ALLOWED_BIDDERS: usize = 32;
+/// Longest banner name accepted, in bytes.
+const MAX_NAME_LENGTH: usize = 64;
+/// Longest image url accepted, in bytes.
+const MAX_URL_LENGTH: usize = 256;
+/// Longest description accepted, in bytes.
+const MAX_DESC_LENGTH: usize = 1024;
+/// Longest message accepted with a `gift`, in bytes.
+const MAX_GIFT_MESSAGE_LENGTH: usize = 256;
+/// Most banners `ImageUrlIndex` lists under a single url.
+const MAX_BANNERS_PER_IMAGE: usize = 16;
+/// Most translations a single banner may carry.
+const MAX_LANGUAGES: usize = 16;
+/// Most ownership records kept per banner; the oldest are dropped first.
+const MAX_HISTORY: usize = 32;
+/// Longest bid increment schedule accepted by `set_bid_increment_tiers`.
+const MAX_INCREMENT_TIERS: usize = 8;
 
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -13,15 +52,214 @@ pub struct Banner<Hash, Balance, AccountId, BlockNumber> {
     id: Hash,
     name: Vec<u8>,
     image_url: Vec<u8>,
+    /// Hash of the file at `image_url`, so viewers can check what they fetched.
+    content_hash: Hash,
     desc: Vec<u8>,
     current_price: Balance,
     current_bidder: AccountId,
     can_bid: bool,
     bid_end_height: BlockNumber,
+    /// Set by the first bid from someone other than the owner, so settlement doesn't have to
+    /// infer it from `current_bidder`.
+    has_real_bid: bool,
+    /// Set by the first sale; from then on the creator can no longer change the royalty.
+    royalty_locked: bool,
+}
+
+/// Which side absorbs rounding when royalty and commission don't divide a payment evenly. The
+/// seller always receives whatever is left, so the three legs sum exactly to the payment.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum RoundingPolicy {
+    /// Royalty and commission are rounded down.
+    FavorSeller,
+    /// Royalty and commission are rounded up.
+    FavorFees,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        RoundingPolicy::FavorSeller
+    }
+}
+
+/// Who bears the commission on a sale.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum FeePayer {
+    /// The commission is deducted from the seller's proceeds.
+    Seller,
+    /// The commission is charged to the buyer on top of the price.
+    Buyer,
+}
+
+impl Default for FeePayer {
+    fn default() -> Self {
+        FeePayer::Seller
+    }
+}
+
+/// An installment auction that has ended and is waiting on its winner to pay the rest.
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PendingPayment<AccountId, Balance, BlockNumber> {
+    pub winner: AccountId,
+    pub price: Balance,
+    /// Already reserved from the winner, and forfeited to the seller if they don't pay.
+    pub deposit: Balance,
+    /// Last block `finalize_payment` is accepted at.
+    pub deadline: BlockNumber,
+    /// Offered the banner at their own bid if the winner doesn't pay.
+    pub runner_up: Option<(AccountId, Balance)>,
+}
+
+/// A banner's descriptive fields, as captured when it was listed for auction.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BannerMetadata {
+    pub name: Vec<u8>,
+    pub image_url: Vec<u8>,
+    pub desc: Vec<u8>,
+}
+
+/// Snapshot of a live auction, as returned by `auction_status`.
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AuctionStatus<AccountId, Balance, BlockNumber> {
+    pub current_price: Balance,
+    /// `None` until somebody other than the owner has bid.
+    pub current_bidder: Option<AccountId>,
+    pub bid_end_height: BlockNumber,
+    /// Time left until bidding closes, at `SecondsPerBlock`.
+    pub seconds_remaining: u64,
+    /// The runner-up: the highest bid from anyone but the current high bidder, if any.
+    pub previous_bidder: Option<AccountId>,
+    pub previous_price: Balance,
+    /// What the banner looked like when it was listed, regardless of later edits.
+    pub listing: BannerMetadata,
+}
+
+/// A live auction as listed by `active_auctions_detailed`.
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AuctionView<Hash, AccountId, Balance, BlockNumber> {
+    pub banner_id: Hash,
+    pub current_price: Balance,
+    /// `None` until somebody other than the owner has bid.
+    pub current_bidder: Option<AccountId>,
+    pub bid_end_height: BlockNumber,
+    /// Blocks until bidding closes, or zero once it has.
+    pub blocks_remaining: BlockNumber,
+}
+
+/// Everything a banner detail page shows, as returned by `banner_detail`.
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BannerDetail<Hash, Balance, AccountId, BlockNumber> {
+    pub banner: Banner<Hash, Balance, AccountId, BlockNumber>,
+    pub owner: AccountId,
+    /// `None` unless the banner is on auction.
+    pub auction: Option<AuctionStatus<AccountId, Balance, BlockNumber>>,
+    /// Blocks until bidding closes; zero if it has closed or there is no auction.
+    pub blocks_remaining: BlockNumber,
+    /// Price of the most recent completed sale, by auction or offer.
+    pub last_sale_price: Option<Balance>,
+    /// Whether root has marked the banner as official.
+    pub verified: bool,
+}
+
+/// Transition reported by `AuctionStateChanged`, so indexers can follow every auction through a
+/// single event stream.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum AuctionState {
+    Started,
+    Bid,
+    Extended,
+    Ended,
+    Aborted,
+}
+
+/// Where a live auction stands relative to the current block.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+enum AuctionPhase {
+    /// Before `bid_end_height`: bids are accepted.
+    Bidding,
+    /// From `bid_end_height` until the grace period elapses: neither bids nor settlement.
+    AwaitingSettlement,
+    /// At or after `bid_end_height` plus the grace period: the auction can be settled.
+    Settleable,
+}
+
+/// How `settle` closed an auction.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+enum AuctionOutcome<AccountId, Balance> {
+    /// The banner went to `winner` for `price`.
+    Sold { winner: AccountId, price: Balance },
+    /// Nobody outbid the starting price, so the banner stayed with the seller.
+    Aborted,
+    /// An installment auction was won; the banner moves once `winner` pays the rest of `price`.
+    AwaitingPayment { winner: AccountId, price: Balance },
+}
+
+/// Source of reserve price suggestions for listing UIs, e.g. an oracle module. `()` never
+/// suggests anything.
+pub trait PriceOracle<Hash, Balance> {
+    fn suggested_reserve(banner_id: &Hash) -> Option<Balance>;
+}
+
+impl<Hash, Balance> PriceOracle<Hash, Balance> for () {
+    fn suggested_reserve(_banner_id: &Hash) -> Option<Balance> {
+        None
+    }
+}
+
+/// Whether an account may place bids above `HighValueThreshold`, e.g. backed by a KYC module.
+/// `()` attests everyone.
+pub trait AttestationCheck<AccountId> {
+    fn is_attested(who: &AccountId) -> bool;
+}
+
+impl<AccountId> AttestationCheck<AccountId> for () {
+    fn is_attested(_who: &AccountId) -> bool {
+        true
+    }
+}
+
+/// Notified whenever an auction settles, e.g. by a rewards module. `buyer` is `None` and `price`
+/// zero when the auction was aborted; `outcome` is `Ended` or `Aborted`. `()` does nothing.
+pub trait OnAuctionSettled<Hash, AccountId, Balance> {
+    fn on_auction_settled(banner_id: &Hash, seller: &AccountId, buyer: Option<&AccountId>, price: Balance, outcome: AuctionState);
+}
+
+impl<Hash, AccountId, Balance> OnAuctionSettled<Hash, AccountId, Balance> for () {
+    fn on_auction_settled(_banner_id: &Hash, _seller: &AccountId, _buyer: Option<&AccountId>, _price: Balance, _outcome: AuctionState) {}
+}
+
+/// Minimal non-fungible token interface, so other modules can hold or move banners without
+/// depending on this module's storage or calls.
+pub trait Nft<AccountId> {
+    type Id;
+
+    fn exists(id: &Self::Id) -> bool;
+    fn owner(id: &Self::Id) -> Option<AccountId>;
+    /// Moves `id` from `from` to `to`, with the same checks as any other banner transfer.
+    fn transfer(from: &AccountId, to: &AccountId, id: &Self::Id) -> Result;
 }
 
 pub trait Trait: balances::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+    /// Where `suggested_reserve` gets its prices from.
+    type PriceOracle: PriceOracle<Self::Hash, Self::Balance>;
+
+    /// Who may place bids above `HighValueThreshold`.
+    type AttestationCheck: AttestationCheck<Self::AccountId>;
+
+    /// Told about every `Deal` and `Abort`.
+    type OnAuctionSettled: OnAuctionSettled<Self::Hash, Self::AccountId, Self::Balance>;
 }
 
 decl_event!(
@@ -29,6 +267,7 @@ decl_event!(
     where
         <T as system::Trait>::AccountId,
         <T as system::Trait>::Hash,
+        <T as system::Trait>::BlockNumber,
         <T as balances::Trait>::Balance
     {
         CreateBanner(AccountId, Hash),
@@ -37,13 +276,121 @@ decl_event!(
         Transferred(AccountId, AccountId, Hash),
         Deal(AccountId, Hash, Balance),
         Abort(AccountId, Hash),
+        Burned(AccountId, Hash),
+        Redeemed(AccountId, Hash, Balance),
+        MetadataUpdated(AccountId, Hash),
+        AuctionStateChanged(Hash, AuctionState),
+        LateBid(Hash, AccountId, BlockNumber),
+        AuctionExtended(Hash, BlockNumber),
+        AuctionRepriced(Hash, Balance),
+        OfferMade(AccountId, Hash, Balance),
+        OfferAccepted(AccountId, AccountId, Hash, Balance),
+        OfferWithdrawn(AccountId, Hash),
+        Listed(AccountId, Hash, Balance),
+        Sold(AccountId, Hash, Balance),
+        Delisted(AccountId, Hash),
+        RoyaltyCapped(Hash, u32),
+        RoyaltySet(Hash, u32),
+        VerificationChanged(Hash, bool),
+        Gifted(AccountId, AccountId, Hash, Vec<u8>),
+        Retired(AccountId, Hash),
+        AuctionManagerSet(Hash, Option<AccountId>),
+        Reclaimed(AccountId, Hash),
+        CancelAuction(AccountId, Hash),
+        /// The high bidder refunded and the penalty the seller paid them.
+        AuctionForceCancelled(Hash, AccountId, Balance),
+        /// The winner who declined, the runner-up the banner was offered to instead, if any, and
+        /// the penalty the seller kept. The commission, royalty and buyer's fee on the declined
+        /// bid are not refunded either.
+        WinDeclined(Hash, AccountId, Option<AccountId>, Balance),
+        /// Banners were moved from the first account to the second; the count is how many the
+        /// first account still holds.
+        AccountMigrated(AccountId, AccountId, u64),
+        AccountBlocked(AccountId, bool),
+        /// The account that has to call `finalize_payment`, the amount still owed and the last
+        /// block it can pay at.
+        PaymentDue(Hash, AccountId, Balance, BlockNumber),
+        /// The winner who didn't pay and the deposit the seller kept.
+        PaymentForfeited(Hash, AccountId, Balance),
     }
 );
 
+decl_runtime_apis! {
+    /// Read-only queries over the banners module for off-chain services.
+    pub trait BannersApi<AccountId, Hash, Balance, BlockNumber> where
+        AccountId: Codec,
+        Hash: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        /// Live auctions `who` is selling or currently leading, with their end heights.
+        fn auctions_ending_for(who: AccountId) -> Vec<(Hash, BlockNumber)>;
+        /// Whether `name` is valid and not already used by another banner.
+        fn is_name_available(name: Vec<u8>) -> bool;
+        /// Total number of banners and the number of distinct accounts holding at least one.
+        fn collection_stats() -> (u64, u64);
+        /// The `index`th banner owned by `owner`, or `None` if `index` is out of range.
+        fn owned_banner_at(owner: AccountId, index: u64) -> Option<Banner<Hash, Balance, AccountId, BlockNumber>>;
+        /// The current high bidder of a live auction, or `None` if nobody has bid yet.
+        fn current_bidder_of(banner_id: Hash) -> Option<AccountId>;
+        /// How a sale of `banner_id` at `sale_price` would be split, as
+        /// `(seller, royalty, commission)`.
+        fn estimate_proceeds(banner_id: Hash, sale_price: Balance) -> (Balance, Balance, Balance);
+        /// The state of the auction for `banner_id`, or `None` if it isn't on auction.
+        fn auction_status(banner_id: Hash) -> Option<AuctionStatus<AccountId, Balance, BlockNumber>>;
+        /// A reserve price suggested by the configured oracle, if it has one.
+        fn suggested_reserve(banner_id: Hash) -> Option<Balance>;
+        /// The most-bid-on live auctions, busiest first.
+        fn top_banners_by_bids(limit: u32) -> Vec<(Hash, u32)>;
+        /// Every recorded owner of `banner_id` and the block they acquired it at, oldest first.
+        fn provenance(banner_id: Hash) -> Vec<(AccountId, BlockNumber)>;
+        /// How much of `who`'s balance is locked in outstanding offers, listing deposits and
+        /// installment bid deposits.
+        fn reserved_of(who: AccountId) -> Balance;
+        /// Live auctions on which `who` is currently the high bidder.
+        fn bids_of(who: AccountId) -> Vec<Hash>;
+        /// A page of live auctions with the blocks left on each.
+        fn active_auctions_detailed(start: u64, limit: u64) -> Vec<AuctionView<Hash, AccountId, Balance, BlockNumber>>;
+        /// The banner, its owner, auction and sale price in one read, or `None` if it doesn't exist.
+        fn banner_detail(banner_id: Hash) -> Option<BannerDetail<Hash, Balance, AccountId, BlockNumber>>;
+        /// Runs `create_banner`'s metadata checks, returning the first failure's message.
+        fn validate_metadata(name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>) -> rstd::result::Result<(), Vec<u8>>;
+        /// Each of the first `MAX_BANNERS_BY_IDS` requested banners, or `None` for ids that don't
+        /// exist, in request order.
+        fn banners_by_ids(ids: Vec<Hash>) -> Vec<(Hash, Option<Banner<Hash, Balance, AccountId, BlockNumber>>)>;
+        /// The banner's name and description in `lang`, falling back to its own.
+        fn localized_metadata(banner_id: Hash, lang: [u8; 2]) -> (Vec<u8>, Vec<u8>);
+        /// A page of every banner, and the cursor to pass for the next page.
+        fn banners_page(cursor: Option<u64>, limit: u64) -> (Vec<Hash>, Option<u64>);
+        /// A page of the banners `owner` holds, and the cursor to pass for the next page.
+        fn banners_of(owner: AccountId, cursor: Option<u64>, limit: u64) -> (Vec<Hash>, Option<u64>);
+        /// How many banners existed as of the latest supply sample at or before `block`.
+        fn supply_at(block: BlockNumber) -> Option<u64>;
+        /// Banners using `url` as their image, so UIs can flag copies.
+        fn banners_with_image(url: Vec<u8>) -> Vec<Hash>;
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as BannerStorage {
         Banners get(banner): map T::Hash => Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>;
         BannerOwner get(owner_of): map T::Hash => Option<T::AccountId>;
+        /// Banner ids by name, keyed by `name_key` so case variants collide when
+        /// `CaseInsensitiveNames` is set.
+        BannerByName get(banner_by_name): map Vec<u8> => Option<T::Hash>;
+        /// Banners using each image url, so copies can be flagged. Banners past the first
+        /// `MAX_BANNERS_PER_IMAGE` on a url aren't listed.
+        ImageUrlIndex get(image_url_index): map Vec<u8> => Vec<T::Hash>;
+        /// Account that minted each banner, which earns royalties on resales.
+        Creator get(creator_of): map T::Hash => Option<T::AccountId>;
+        /// Banners each account has ever minted. Unlike `OwnedBannersCount`, transfers and burns
+        /// leave it alone.
+        CreatedCount get(created_count): map T::AccountId => u64;
+        /// Royalty percentage chosen by a banner's creator, overriding `RoyaltyPercent`.
+        BannerRoyalty get(banner_royalty): map T::Hash => Option<u32>;
+        /// Owners of each banner and the block they acquired it at, oldest first. Holds at most
+        /// `MAX_HISTORY` entries.
+        OwnershipHistory get(ownership_history): map T::Hash => Vec<(T::AccountId, T::BlockNumber)>;
 
         AllBannersArray get(banner_by_index): map u64 => T::Hash;
         AllBannersCount get(all_banners_count): u64;
@@ -52,8 +399,192 @@ decl_storage! {
         OwnedBannersArray get(banner_of_owner_by_index): map (T::AccountId, u64) => T::Hash;
         OwnedBannersCount get(owned_banner_count): map T::AccountId => u64;
         OwnedBannersIndex: map T::Hash => u64;
+        /// Number of accounts that currently own at least one banner.
+        DistinctOwnerCount get(distinct_owner_count): u64;
 
         Nonce: u64;
+
+        /// Price each banner last changed hands for, through an auction or an accepted offer.
+        LastSalePrice get(last_sale_price): map T::Hash => Option<T::Balance>;
+        /// Translated name and description of each banner, keyed by two-letter language code.
+        LocalizedMetadata: map (T::Hash, [u8; 2]) => Option<(Vec<u8>, Vec<u8>)>;
+        /// Languages each banner has a translation in, at most `MAX_LANGUAGES`.
+        LocalizedLanguages get(localized_languages): map T::Hash => Vec<[u8; 2]>;
+
+        /// Commission paid to `FeeAccount` over the chain's lifetime, by sellers and buyers.
+        TotalFeesCollected get(total_fees_collected): T::Balance;
+        /// Royalties paid to creators over the chain's lifetime.
+        TotalRoyaltiesPaid get(total_royalties_paid): T::Balance;
+
+        /// Banners marked as official by root. Kept when the banner changes hands.
+        Verified get(is_verified): map T::Hash => bool;
+
+        /// Amount paid out of the reward pool to whoever burns the banner.
+        RedeemValue get(redeem_value): map T::Hash => T::Balance;
+        /// Account that funds redeem payouts.
+        RewardPool get(reward_pool) config(): T::AccountId;
+
+        /// Non-refundable fee charged to the bidder on every bid, on top of the bid itself.
+        BidDeposit get(bid_deposit) config(): T::Balance;
+        /// Account that receives bid deposits.
+        FeeAccount get(fee_account) config(): T::AccountId;
+        /// Account that holds retired banners. Nobody should hold its key.
+        BurnAddress get(burn_address) config(): T::AccountId;
+        /// Banners handed to `BurnAddress` by `retire`; they can never move again.
+        Retired get(is_retired): map T::Hash => bool;
+
+        /// Bids above this price need the bidder to pass `T::AttestationCheck`. Zero disables
+        /// the check.
+        HighValueThreshold get(high_value_threshold) config(): T::Balance;
+
+        /// Longest an auction may run for, counted from the current block. Zero disables the cap.
+        MaxAuctionDuration get(max_auction_duration) config(): T::BlockNumber;
+        /// Shortest an auction may be listed for. Zero-length auctions are always rejected.
+        MinAuctionDuration get(min_auction_duration) config(): T::BlockNumber;
+
+        /// `(threshold, increment)` pairs in ascending threshold order. A bid must beat the current
+        /// price by the increment of the last tier whose threshold the price has reached. Empty
+        /// means any higher bid is accepted.
+        BidIncrementTiers get(bid_increment_tiers) config(): Vec<(T::Balance, T::Balance)>;
+
+        /// Blocks an account must wait between two bids on the same banner. Zero disables it.
+        BidCooldown get(bid_cooldown) config(): T::BlockNumber;
+        /// Block of each account's latest bid on each live auction, while `BidCooldown` is set.
+        /// Cleared for the whole auction on settlement.
+        LastBidBlock: double_map T::Hash, blake2_256(T::AccountId) => Option<T::BlockNumber>;
+
+        /// Blocks a banner must wait after an auction ends before it can be auctioned again.
+        /// Zero disables it. Auto-relisting, which the seller opted into when listing, is exempt.
+        ReauctionCooldown get(reauction_cooldown) config(): T::BlockNumber;
+        /// Block at which each banner's latest auction ended.
+        LastAuctionEnd get(last_auction_end): map T::Hash => Option<T::BlockNumber>;
+        /// Auctions each banner has been put up for, relists included.
+        AuctionCount get(auction_count): map T::Hash => u32;
+        /// Winner, price and end block of each banner's latest auction, if it ended in a sale.
+        /// Cleared when the banner goes back on auction.
+        AuctionResult get(last_result): map T::Hash => Option<(T::AccountId, T::Balance, T::BlockNumber)>;
+
+        /// Final blocks of an auction in which a bid also emits `LateBid`. Zero disables it.
+        AntiSnipeWindow get(anti_snipe_window) config(): T::BlockNumber;
+
+        /// Percentage of every sale paid to `FeeAccount`.
+        CommissionPercent get(commission_percent) config(): u32;
+        /// Percentage of the high bid a seller pays the high bidder, on top of a full refund,
+        /// for pulling an auction with `force_cancel_auction`.
+        ForceCancelPenaltyPercent get(force_cancel_penalty_percent) config(): u32;
+        /// Percentage of the winning bid a winner forfeits to the seller for backing out with
+        /// `decline_win`. This is on top of the commission, royalty and buyer's fee, which are
+        /// kept by design: the fee account and creator are never asked to give money back.
+        DeclinePenaltyPercent get(decline_penalty_percent) config(): u32;
+        /// Percentage of every resale paid to the banner's creator.
+        RoyaltyPercent get(royalty_percent) config(): u32;
+        /// Highest royalty percentage actually charged, whatever `RoyaltyPercent` says. Set by
+        /// root.
+        MaxRoyaltyPercent get(max_royalty_percent) config(): u32;
+        /// How royalty and commission are rounded.
+        Rounding get(rounding_policy) config(): RoundingPolicy;
+        /// Whether the commission comes out of the seller's proceeds or on top of the buyer's bid.
+        CommissionPayer get(fee_payer) config(): FeePayer;
+
+        /// Blocks after `bid_end_height` before an ended auction may be settled.
+        SettlementGracePeriod get(settlement_grace_period) config(): T::BlockNumber;
+
+        /// Direct offers outside of an auction, keyed by banner and buyer. The amount is held in
+        /// the buyer's reserved balance until the offer is accepted or withdrawn.
+        Offers get(offer): map (T::Hash, T::AccountId) => T::Balance;
+        /// Asking price of banners listed for sale outside of an auction. Cleared whenever the
+        /// banner changes hands.
+        FixedPrice get(fixed_price): map T::Hash => Option<T::Balance>;
+
+        /// Number of outstanding offers on each banner.
+        OfferCount get(offer_count): map T::Hash => u32;
+        /// Live auctions each account is the high bidder on, at most `MAX_LEADING_BIDS`.
+        BidsByAccount get(bids_by_account): map T::AccountId => Vec<T::Hash>;
+        /// Total each account has reserved across its outstanding offers, listing deposits and
+        /// installment bid deposits.
+        ReservedByBidder get(reserved_by_bidder): map T::AccountId => T::Balance;
+        /// Most outstanding offers a single banner may have. Zero disables the cap.
+        MaxOffersPerBanner get(max_offers_per_banner) config(): u32;
+
+        /// Reserved from the seller when an auction starts and returned when it settles, or
+        /// handed to the high bidder if the seller pulls it with `force_cancel_auction`. Zero
+        /// disables it.
+        ListingDeposit get(listing_deposit) config(): T::Balance;
+        /// Who put up the listing deposit on each live auction, and how much. Kept across
+        /// automatic relists.
+        HeldListingDeposit get(held_listing_deposit): map T::Hash => Option<(T::AccountId, T::Balance)>;
+
+        /// Percentage of a bid reserved from the bidder instead of paid out when they bid. The
+        /// winner pays the rest with `finalize_payment`. Zero keeps auctions pay-as-you-bid.
+        InstallmentDepositPercent get(installment_deposit_percent) config(): u32;
+        /// Blocks an installment auction's winner has after settlement to pay the rest.
+        PaymentWindow get(payment_window) config(): T::BlockNumber;
+        /// The deposit the high bidder on each installment auction has reserved.
+        BidEscrow get(bid_escrow): map T::Hash => Option<(T::AccountId, T::Balance)>;
+        /// Installment auctions that have been settled but not yet paid for. The banner can't
+        /// change hands or be relisted until it is paid for or the payment is forfeited.
+        AwaitingPayment get(awaiting_payment): map T::Hash => Option<PendingPayment<T::AccountId, T::Balance, T::BlockNumber>>;
+
+        /// Bids placed in the banner's current or most recent auction; reset on relisting.
+        BidCount get(bid_count): map T::Hash => u32;
+        /// Set once a banner is first put up for auction, so bids on it can be told apart from
+        /// bids on a banner that was never listed.
+        Auctioned get(was_auctioned): map T::Hash => bool;
+
+        /// The runner-up on each live auction: the highest bid from anyone but the current high
+        /// bidder. A high bidder raising their own bid doesn't displace it.
+        PreviousBid get(previous_bid): map T::Hash => Option<(T::AccountId, T::Balance)>;
+
+        /// Metadata of each live auction's banner as of listing.
+        ListingMetadata get(listing_metadata): map T::Hash => BannerMetadata;
+
+        /// Accounts invited to bid on a private auction. Empty for public auctions. Holds at most
+        /// `MAX_ALLOWED_BIDDERS` entries.
+        AllowedBidders get(allowed_bidders): map T::Hash => Vec<T::AccountId>;
+        /// Account the owner lets run auctions of each banner on their behalf. Cleared when the
+        /// banner changes hands.
+        AuctionManager get(auction_manager): map T::Hash => Option<T::AccountId>;
+        /// Lowest and highest bid a seller will accept on their live auction, if they set either.
+        BidLimits get(bid_limits): map T::Hash => (Option<T::Balance>, Option<T::Balance>);
+        /// Relists left and the price cut for each, for auctions that relist when they end
+        /// without bids.
+        AutoRelist get(auto_relist): map T::Hash => Option<(u32, T::Balance)>;
+
+        /// Every live auction, in no particular order. Ended auctions leave the set when they are
+        /// settled.
+        ActiveAuctionsArray get(active_auction_by_index): map u64 => T::Hash;
+        ActiveAuctionsCount get(active_auctions_count): u64;
+        ActiveAuctionsIndex: map T::Hash => u64;
+
+        /// Number of live auctions each account is currently selling.
+        ActiveAuctionCount get(active_auction_count): map T::AccountId => u32;
+        /// Most live auctions a single account may sell at once. Zero disables the cap.
+        MaxActiveAuctionsPerAccount get(max_active_auctions_per_account) config(): u32;
+
+        /// Prefixes an image url must start with, e.g. `ipfs://`. An empty list accepts any url.
+        AllowedUrlSchemes get(allowed_url_schemes) config(): Vec<Vec<u8>>;
+
+        /// Whether bids that would leave the bidder below the existential deposit are rejected.
+        RequireLiveBidders get(require_live_bidders) config(): bool;
+
+        /// Whether names that differ only in ASCII case count as the same name.
+        CaseInsensitiveNames get(case_insensitive_names) config(): bool;
+
+        /// Accounts root has barred from minting, listing, bidding and receiving banners.
+        /// Auctions they are already part of still settle.
+        Blocked get(is_blocked): map T::AccountId => bool;
+
+        /// `AllBannersCount` at the end of every block that is a multiple of
+        /// `SupplySampleInterval`.
+        SupplyHistory get(supply_history): map T::BlockNumber => Option<u64>;
+        /// Blocks between `SupplyHistory` samples. Zero disables sampling.
+        SupplySampleInterval get(supply_sample_interval) config(): T::BlockNumber;
+        /// Most `SupplyHistory` samples kept; older ones are pruned as new ones are taken. Zero
+        /// keeps every sample.
+        SupplyRetention get(supply_retention) config(): u32;
+
+        /// Target block time, used to turn blocks remaining into seconds for frontends.
+        SecondsPerBlock get(seconds_per_block) config(): u64;
     }
 }
 
@@ -62,47 +593,192 @@ decl_module! {
         
         fn deposit_event<T>() = default;
 
-        fn create_banner(origin, name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>) -> Result {
-            let sender = ensure_signed(origin)?;
-            let nonce = <Nonce<T>>::get();
-            let random_hash = (<system::Module<T>>::random_seed(), &sender, nonce)
-                .using_encoded(<T as system::Trait>::Hashing::hash);
-
-            let new_banner = Banner {
-                id: random_hash,
-                name: name,
-                image_url: url,
-                desc: desc,
-                current_price: <T::Balance as As<u64>>::sa(0),
-                current_bidder:  sender.clone(),
-                bid_end_height: <T::BlockNumber as As<u64>>::sa(0),
-                can_bid: false,
-            };
+        fn on_finalize(n: T::BlockNumber) {
+            let interval = Self::supply_sample_interval();
+            if interval.is_zero() || !(n % interval).is_zero() {
+                return;
+            }
+
+            <SupplyHistory<T>>::insert(n, Self::all_banners_count());
+
+            let retention = Self::supply_retention();
+            if retention > 0 {
+                let window = interval.checked_mul(&Self::blocks(retention as u64));
+                if let Some(expired) = window.and_then(|window| n.checked_sub(&window)) {
+                    <SupplyHistory<T>>::remove(expired);
+                }
+            }
+        }
+
+        fn create_banner(origin, name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>, content_hash: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+
+            Self::create(sender, name, url, desc, content_hash)?;
+
+            Ok(())
+        }
+
+        /// Mints a banner for `owner` that pays `redeem_value` out of the reward pool when it is
+        /// burned. Only root may attach a redeem value, since it draws on shared funds.
+        fn create_redeemable_banner(origin, owner: T::AccountId, name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>, redeem_value: T::Balance, content_hash: T::Hash) -> Result {
+            ensure_root(origin)?;
+            Self::ensure_not_blocked(&owner)?;
+
+            let banner_id = Self::create(owner, name, url, desc, content_hash)?;
+            <RedeemValue<T>>::insert(banner_id, redeem_value);
+
+            Ok(())
+        }
+
+        fn set_max_royalty_percent(origin, percent: u32) -> Result {
+            ensure_root(origin)?;
+
+            ensure!(percent <= 100, "Royalty cap can't exceed 100 percent");
+            <MaxRoyaltyPercent<T>>::put(percent);
+
+            Ok(())
+        }
+
+        fn set_bid_increment_tiers(origin, tiers: Vec<(T::Balance, T::Balance)>) -> Result {
+            ensure_root(origin)?;
+
+            ensure!(tiers.len() <= MAX_INCREMENT_TIERS, "Too many bid increment tiers");
+            ensure!(tiers.windows(2).all(|pair| pair[0].0 < pair[1].0), "Bid increment tiers must be in ascending threshold order");
+            <BidIncrementTiers<T>>::put(tiers);
+
+            Ok(())
+        }
+
+        fn set_verified(origin, banner_id: T::Hash, verified: bool) -> Result {
+            ensure_root(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+            <Verified<T>>::insert(banner_id, verified);
+
+            Self::deposit_event(RawEvent::VerificationChanged(banner_id, verified));
+
+            Ok(())
+        }
+
+        fn set_blocked(origin, who: T::AccountId, blocked: bool) -> Result {
+            ensure_root(origin)?;
+
+            if blocked {
+                <Blocked<T>>::insert(&who, true);
+            } else {
+                <Blocked<T>>::remove(&who);
+            }
+
+            Self::deposit_event(RawEvent::AccountBlocked(who, blocked));
+
+            Ok(())
+        }
+
+        /// Moves up to `MAX_MIGRATE_BATCH` of `from`'s banners to `to`, e.g. after a key rotation.
+        /// Call again until `AccountMigrated` reports nothing left. Auctions nobody has bid on
+        /// are aborted; auctions with bids carry on with `to` as the seller, so their bidders
+        /// are unaffected. Fails without moving anything if `to` is blocked or a banner in the
+        /// batch is retired or awaiting payment.
+        fn migrate_account(origin, from: T::AccountId, to: T::AccountId) -> Result {
+            ensure_root(origin)?;
+
+            ensure!(from != to, "'from' and 'to' are the same account");
+            Self::ensure_not_blocked(&to)?;
+
+            let count = Self::owned_banner_count(&from);
+            let batch = count.saturating_sub(MAX_MIGRATE_BATCH)..count;
+
+            // The same checks as any other transfer, made for the whole batch before anything moves.
+            for index in batch.clone() {
+                let banner_id = Self::banner_of_owner_by_index((from.clone(), index));
+                ensure!(!Self::is_retired(banner_id), "This banner has been retired");
+                Self::ensure_not_awaiting_payment(banner_id)?;
+            }
+
+            for index in batch.rev() {
+                let banner_id = Self::banner_of_owner_by_index((from.clone(), index));
+                let banner = Self::banner(banner_id);
+
+                if banner.can_bid && !banner.has_real_bid {
+                    // Relisting would leave the banner on auction under `from`.
+                    <AutoRelist<T>>::remove(banner_id);
+                    Self::settle(from.clone(), banner_id, banner)?;
+                } else if banner.can_bid {
+                    <ActiveAuctionCount<T>>::mutate(&from, |n| *n = n.saturating_sub(1));
+                    <ActiveAuctionCount<T>>::mutate(&to, |n| *n = n.saturating_add(1));
+                }
+
+                Self::reassign(from.clone(), to.clone(), banner_id)?;
+            }
+
+            Self::deposit_event(RawEvent::AccountMigrated(from.clone(), to, Self::owned_banner_count(&from)));
+
+            Ok(())
+        }
 
-            Self::mint(sender, random_hash, new_banner)?;
+        /// Moves a banner whose owner's account has been reaped to the reward pool, so it isn't
+        /// stranded with an owner who may never return.
+        fn reclaim_orphaned(origin, banner_id: T::Hash) -> Result {
+            ensure_root(origin)?;
 
-            <Nonce<T>>::mutate(|n| *n += 1);
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(<balances::Module<T> as Currency<_>>::total_balance(&owner).is_zero(), "The owner's account still exists");
+
+            Self::transfer_from(owner.clone(), Self::reward_pool(), banner_id)?;
+
+            Self::deposit_event(RawEvent::Reclaimed(owner, banner_id));
 
             Ok(())
         }
 
-        fn set_image_url(origin, banner_id: T::Hash, new_url: Vec<u8>) -> Result {
+        /// Lets `manager` start, extend, reprice and cancel auctions of the banner for its owner,
+        /// and accept the highest bid, while the owner still receives the proceeds. `None`
+        /// revokes the current manager.
+        fn set_auction_manager(origin, banner_id: T::Hash, manager: Option<T::AccountId>) -> Result {
             let sender = ensure_signed(origin)?;
 
             ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
 
             let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
             ensure!(owner == sender, "You do not own this banner");
+            // A blocked owner may still revoke a manager, just not appoint one.
+            if manager.is_some() {
+                Self::ensure_not_blocked(&owner)?;
+            }
 
-            let mut banner = Self::banner(banner_id);
-            banner.image_url = new_url;
+            match manager {
+                Some(ref manager) => <AuctionManager<T>>::insert(banner_id, manager),
+                None => <AuctionManager<T>>::remove(banner_id),
+            }
 
-            <Banners<T>>::insert(banner_id, banner);
+            Self::deposit_event(RawEvent::AuctionManagerSet(banner_id, manager));
+
+            Ok(())
+        }
+
+        /// Lets the creator set the banner's royalty until it is first sold.
+        fn set_royalty(origin, banner_id: T::Hash, percent: u32) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let creator = Self::creator_of(banner_id).ok_or("No creator for this banner")?;
+            ensure!(creator == sender, "Only the creator can set the royalty");
+            ensure!(!Self::banner(banner_id).royalty_locked, "Royalty is locked after the first sale");
+            ensure!(percent <= 100, "Royalty can't exceed 100 percent");
+
+            <BannerRoyalty<T>>::insert(banner_id, percent);
+
+            Self::deposit_event(RawEvent::RoyaltySet(banner_id, percent));
 
             Ok(())
         }
 
-        fn auction_banner(origin, banner_id: T::Hash, starting_price: T::Balance) -> Result {
+        /// Points the banner at a new image, along with the hash of its content.
+        fn set_image_url(origin, banner_id: T::Hash, new_url: Vec<u8>, content_hash: T::Hash) -> Result {
             let sender = ensure_signed(origin)?;
 
             ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
@@ -110,132 +786,5184 @@ decl_module! {
             let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
             ensure!(owner == sender, "You do not own this banner");
 
+            Self::validate_url(&new_url)?;
+
             let mut banner = Self::banner(banner_id);
-            ensure!(banner.can_bid == false, "This banner has already been auctioned");
+            Self::unindex_image_url(&banner.image_url, banner_id);
+            Self::index_image_url(&new_url, banner_id);
+            banner.image_url = new_url;
+            banner.content_hash = content_hash;
 
-            banner.current_price = starting_price;
-            banner.can_bid = true;
-            banner.current_bidder = sender.clone();
-            banner.bid_end_height = <system::Module<T>>::block_number() + <T::BlockNumber as As<u64>>::sa(AUCTION_DURATION);
-            
             <Banners<T>>::insert(banner_id, banner);
 
-            Self::deposit_event(RawEvent::StartAuction(sender, banner_id, starting_price));
-
             Ok(())
         }
 
-        fn bid(origin, banner_id: T::Hash, bid_price: T::Balance) -> Result {
+        /// Adds or replaces the banner's name and description in `lang`. Translated names don't
+        /// have to be unique.
+        fn set_localized_metadata(origin, banner_id: T::Hash, lang: [u8; 2], name: Vec<u8>, desc: Vec<u8>) -> Result {
             let sender = ensure_signed(origin)?;
 
             ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
             let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
 
-            let mut banner = Self::banner(banner_id);
-            ensure!(banner.can_bid, "This banner can't be bid");
+            Self::validate_name(&name)?;
+            Self::validate_desc(&desc)?;
 
-            if banner.bid_end_height > <system::Module<T>>::block_number() {
-                // still can bid this banner
-                ensure!(owner != sender, "You can't bid your own banner");
-                ensure!(bid_price > banner.current_price, "your bid price must be greater than current price");
+            let mut languages = Self::localized_languages(banner_id);
+            if !languages.contains(&lang) {
+                Self::bounded_push(&mut languages, lang, MAX_LANGUAGES, "Too many translations for this banner")?;
+                <LocalizedLanguages<T>>::insert(banner_id, languages);
+            }
+            <LocalizedMetadata<T>>::insert((banner_id, lang), (name, desc));
 
-                <balances::Module<T> as Currency<_>>::transfer(&sender, &banner.current_bidder, banner.current_price)?;
-                <balances::Module<T> as Currency<_>>::transfer(&sender, &owner, bid_price - banner.current_price)?;
+            Ok(())
+        }
 
-                banner.current_bidder = sender.clone();
-                banner.current_price = bid_price;
+        /// Updates any of the name, image url and description of a banner in one call. Fields
+        /// passed as `None` are left unchanged.
+        fn update_metadata(origin, banner_id: T::Hash, name: Option<Vec<u8>>, url: Option<Vec<u8>>, desc: Option<Vec<u8>>) -> Result {
+            let sender = ensure_signed(origin)?;
 
-                <Banners<T>>::insert(banner_id, banner);
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
 
-                Self::deposit_event(RawEvent::Bid(sender, banner_id, bid_price));
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
 
-            }else {
-                let final_price = banner.current_price;
-                let final_bidder = banner.current_bidder;
+            let mut banner = Self::banner(banner_id);
 
-                banner.can_bid = false;
-                banner.bid_end_height = <T::BlockNumber as As<u64>>::sa(0);
-                banner.current_bidder = final_bidder.clone();
-                banner.current_price = <T::Balance as As<u64>>::sa(0);
-                <Banners<T>>::insert(banner_id, banner);
+            if let Some(ref name) = name {
+                Self::validate_name(name)?;
+                ensure!(Self::banner_by_name(Self::name_key(name)).map_or(true, |id| id == banner_id), "This banner name is already taken");
+            }
+            if let Some(ref url) = url {
+                Self::validate_url(url)?;
+            }
+            if let Some(ref desc) = desc {
+                Self::validate_desc(desc)?;
+            }
 
-                if final_bidder.clone() == owner {
-                    // 流拍
-                    Self::deposit_event(RawEvent::Abort(owner.clone(), banner_id));
-                } else {
-                    // 有效成交
-                    Self::transfer_from(owner.clone(), final_bidder.clone(), banner_id)?;
-                    Self::deposit_event(RawEvent::Deal(final_bidder, banner_id, final_price));
-                }
+            // Every field has been checked, so the indexes can be updated.
+            if let Some(name) = name {
+                <BannerByName<T>>::remove(Self::name_key(&banner.name));
+                <BannerByName<T>>::insert(Self::name_key(&name), banner_id);
+                banner.name = name;
+            }
+            if let Some(url) = url {
+                Self::unindex_image_url(&banner.image_url, banner_id);
+                Self::index_image_url(&url, banner_id);
+                banner.image_url = url;
             }
+            if let Some(desc) = desc {
+                banner.desc = desc;
+            }
+
+            <Banners<T>>::insert(banner_id, banner);
+
+            Self::deposit_event(RawEvent::MetadataUpdated(sender, banner_id));
 
             Ok(())
         }
 
-    }
-}
+        /// Starts an auction. A non-empty `allowed_bidders` makes it private: only those
+        /// accounts may bid.
+        /// `min_bid` and `max_bid`, when given, bound every bid on this auction. With
+        /// `auto_relist` set to `(count, decrement)`, an auction that ends without bids starts
+        /// again `decrement` cheaper, up to `count` times.
+        fn auction_banner(origin, banner_id: T::Hash, starting_price: T::Balance, allowed_bidders: Vec<T::AccountId>, min_bid: Option<T::Balance>, max_bid: Option<T::Balance>, auto_relist: Option<(u32, T::Balance)>) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
 
-impl<T: Trait> Module<T> {
-    fn mint(to: T::AccountId, banner_id: T::Hash, new_banner: Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>) -> Result {
-        ensure!(!<BannerOwner<T>>::exists(banner_id), "banner already exists");
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
 
-        let owned_banner_count = Self::owned_banner_count(&to);
+            let owner = Self::ensure_owner_or_manager(banner_id, &sender)?;
+            Self::ensure_not_blocked(&owner)?;
 
-        let new_owned_banner_count = owned_banner_count.checked_add(1)
-            .ok_or("Overflow adding a new banner to account balance")?;
+            let banner = Self::banner(banner_id);
+            ensure!(banner.can_bid == false, "This banner has already been auctioned");
+            ensure!(!<FixedPrice<T>>::exists(banner_id), "This banner is listed for sale");
+            Self::ensure_below_auction_limit(&owner)?;
+            Self::ensure_reauction_cooldown(banner_id)?;
+            Self::ensure_not_awaiting_payment(banner_id)?;
+            ensure!(allowed_bidders.len() <= MAX_ALLOWED_BIDDERS, "Too many invited bidders");
+            Self::ensure_valid_bid_limits(starting_price, min_bid, max_bid)?;
 
-        let all_banners_count = Self::all_banners_count();
+            // The last check: nothing has been written if the owner can't cover the deposit.
+            Self::reserve_listing_deposit(&owner, banner_id)?;
+            if !allowed_bidders.is_empty() {
+                <AllowedBidders<T>>::insert(banner_id, allowed_bidders);
+            }
+            if min_bid.is_some() || max_bid.is_some() {
+                <BidLimits<T>>::insert(banner_id, (min_bid, max_bid));
+            }
+            if let Some(auto_relist) = auto_relist {
+                <AutoRelist<T>>::insert(banner_id, auto_relist);
+            }
+            Self::start_auction(owner, banner_id, banner, starting_price, Self::blocks(AUCTION_DURATION));
 
-        let new_all_banners_count = all_banners_count.checked_add(1)
-            .ok_or("Overflow adding a new banner to total supply")?;
+            Ok(())
+        }
 
-        <Banners<T>>::insert(banner_id, new_banner);
-        <BannerOwner<T>>::insert(banner_id, &to);
+        fn transfer_and_auction(origin, to: T::AccountId, banner_id: T::Hash, starting_price: T::Balance, duration: T::BlockNumber) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+            Self::ensure_not_blocked(&to)?;
 
-        <AllBannersArray<T>>::insert(all_banners_count, banner_id);
-        <AllBannersCount<T>>::put(new_all_banners_count);
-        <AllBannersIndex<T>>::insert(banner_id, all_banners_count);
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
 
-        <OwnedBannersArray<T>>::insert((to.clone(), owned_banner_count), banner_id);
-        <OwnedBannersCount<T>>::insert(&to, new_owned_banner_count);
-        <OwnedBannersIndex<T>>::insert(banner_id, owned_banner_count);
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
 
-        Self::deposit_event(RawEvent::CreateBanner(to, banner_id));
+            let banner = Self::banner(banner_id);
+            ensure!(banner.can_bid == false, "This banner has already been auctioned");
+            ensure!(!<FixedPrice<T>>::exists(banner_id), "This banner is listed for sale");
+            Self::ensure_below_auction_limit(&to)?;
+            Self::ensure_reauction_cooldown(banner_id)?;
+            Self::ensure_min_duration(duration)?;
+            Self::ensure_within_max_duration(duration)?;
+            // `transfer_from` checks these too, but only after the deposit is reserved.
+            ensure!(sender != to, "'from' and 'to' are the same account");
+            ensure!(!Self::is_retired(banner_id), "This banner has been retired");
+            Self::ensure_not_awaiting_payment(banner_id)?;
 
-        Ok(())
-    }
+            // Everything `start_auction` depends on has been checked, so once ownership has
+            // moved the auction is guaranteed to start as well. The deposit comes from whoever
+            // lists the banner, which here is the old owner.
+            Self::reserve_listing_deposit(&sender, banner_id)?;
+            Self::transfer_from(sender, to.clone(), banner_id)?;
+            Self::start_auction(to, banner_id, banner, starting_price, duration);
 
-    fn transfer_from(from: T::AccountId, to: T::AccountId, banner_id: T::Hash) -> Result {
-        let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            Ok(())
+        }
 
-        ensure!(owner == from, "'from' account does not own this banner");
+        /// Hands the banner to `to` with a note for indexers to show alongside it.
+        fn gift(origin, to: T::AccountId, banner_id: T::Hash, message: Vec<u8>) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+            Self::ensure_not_blocked(&to)?;
 
-        let owned_banner_count_from = Self::owned_banner_count(&from);
-        let owned_banner_count_to = Self::owned_banner_count(&to);
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+            ensure!(message.len() <= MAX_GIFT_MESSAGE_LENGTH, "Gift message is too long");
 
-        let new_owned_banner_count_to = owned_banner_count_to.checked_add(1)
-            .ok_or("Transfer causes overflow of 'to' banner balance")?;
-        let new_owned_banner_count_from = owned_banner_count_from.checked_sub(1)
-            .ok_or("Transfer causes underflow of 'from' banner balance")?;
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
 
-        let banner_index = <OwnedBannersIndex<T>>::get(banner_id);
-        if banner_index != new_owned_banner_count_from {
-            let last_banner_id = <OwnedBannersArray<T>>::get((from.clone(), new_owned_banner_count_from));
-            <OwnedBannersArray<T>>::insert((from.clone(), banner_index), last_banner_id);
-            <OwnedBannersIndex<T>>::insert(last_banner_id, banner_index);
-        }
+            Self::transfer_from(sender.clone(), to.clone(), banner_id)?;
 
-        <BannerOwner<T>>::insert(&banner_id, &to);
-        <OwnedBannersIndex<T>>::insert(banner_id, owned_banner_count_to);
+            Self::deposit_event(RawEvent::Gifted(sender, to, banner_id, message));
 
-        <OwnedBannersArray<T>>::remove((from.clone(), new_owned_banner_count_from));
-        <OwnedBannersArray<T>>::insert((to.clone(), owned_banner_count_to), banner_id);
+            Ok(())
+        }
 
-        <OwnedBannersCount<T>>::insert(&from, new_owned_banner_count_from);
-        <OwnedBannersCount<T>>::insert(&to, new_owned_banner_count_to);
+        /// Settles each auction in `banner_ids` that is ready for settlement, so a relayer can
+        /// close out expired auctions in bulk. Ids that aren't on auction or are still running
+        /// are skipped.
+        fn settle_expired(origin, banner_ids: Vec<T::Hash>) -> Result {
+            ensure_signed(origin)?;
 
-        Self::deposit_event(RawEvent::Transferred(from, to, banner_id));
+            ensure!(banner_ids.len() <= MAX_SETTLE_BATCH, "Too many banners to settle in one call");
 
-        Ok(())
+            for banner_id in banner_ids {
+                if !<Banners<T>>::exists(banner_id) {
+                    continue;
+                }
+
+                let banner = Self::banner(banner_id);
+                if !banner.can_bid || Self::auction_phase(banner.bid_end_height) != AuctionPhase::Settleable {
+                    continue;
+                }
+
+                Self::settle_auction(banner_id)?;
+            }
+
+            Ok(())
+        }
+
+        /// Aborts the caller's auctions that nobody has bid on yet among their `MAX_CANCEL_BATCH`
+        /// banners from index `start`. Auctions with bids keep running. Cancelling doesn't
+        /// reorder the caller's banners, so call again from `start + MAX_CANCEL_BATCH` if more
+        /// remain.
+        fn cancel_all_auctions(origin, start: u64) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            let end = start.saturating_add(MAX_CANCEL_BATCH).min(Self::owned_banner_count(&sender));
+            for index in start..end {
+                let banner_id = Self::banner_of_owner_by_index((sender.clone(), index));
+                let banner = Self::banner(banner_id);
+                if !banner.can_bid || banner.has_real_bid {
+                    continue;
+                }
+
+                // A cancelled auction shouldn't come straight back.
+                <AutoRelist<T>>::remove(banner_id);
+                Self::settle(sender.clone(), banner_id, banner)?;
+                Self::deposit_event(RawEvent::CancelAuction(sender.clone(), banner_id));
+            }
+
+            Ok(())
+        }
+
+        /// Pulls a live auction that already has bids. The seller refunds the high bidder their
+        /// bid and buyer's fee out of their own balance and pays them `ForceCancelPenaltyPercent`
+        /// of the bid on top, along with any listing deposit. Commission and royalty already paid
+        /// stay where they are. An installment bidder just gets their deposit back with the
+        /// penalty.
+        fn force_cancel_auction(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+
+            let mut banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Bidding, "This auction has ended");
+            ensure!(banner.has_real_bid, "This auction has no bids to refund");
+
+            let bidder = banner.current_bidder.clone();
+            let penalty = Self::percent_of(banner.current_price, Self::force_cancel_penalty_percent(), false);
+            // An installment bidder has only reserved a deposit, which settlement releases.
+            let refund = if <BidEscrow<T>>::exists(banner_id) {
+                Self::zero_balance()
+            } else {
+                banner.current_price + Self::buyer_fee(banner.current_price)
+            };
+            let free = <balances::Module<T> as Currency<_>>::free_balance(&sender);
+            ensure!(free >= refund + penalty, "Not enough balance to refund the high bidder");
+            Self::ensure_can_receive(&bidder, refund + penalty)?;
+
+            Self::pay(&sender, &bidder, refund + penalty)?;
+            if let Some((depositor, deposit)) = <HeldListingDeposit<T>>::take(banner_id) {
+                <balances::Module<T> as ReservableCurrency<_>>::repatriate_reserved(&depositor, &bidder, deposit)?;
+                Self::release_reserved(&depositor, deposit);
+            }
+
+            // Without a real bid, settlement aborts and the banner stays with the seller.
+            Self::stop_leading(&bidder, banner_id);
+            <AutoRelist<T>>::remove(banner_id);
+            banner.has_real_bid = false;
+            Self::settle(owner, banner_id, banner)?;
+
+            Self::deposit_event(RawEvent::AuctionForceCancelled(banner_id, bidder, penalty));
+
+            Ok(())
+        }
+
+        /// Lets the winner of an ended auction back out during the `SettlementGracePeriod`. The
+        /// seller refunds the share of the bid they received, less `DeclinePenaltyPercent` of the
+        /// bid, which they keep. The commission, royalty and buyer's fee already paid on the bid
+        /// are intentionally not refunded, as they were never the seller's to return: the winner
+        /// forfeits them along with the penalty. The runner-up is then offered the banner at
+        /// their own bid through `finalize_payment`, which they are free to ignore. Without a
+        /// runner-up the banner stays with the seller.
+        fn decline_win(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            let mut banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::AwaitingSettlement, "This auction is not awaiting settlement");
+            ensure!(banner.has_real_bid && banner.current_bidder == sender, "Only the winning bidder can decline this banner");
+            ensure!(!<BidEscrow<T>>::exists(banner_id), "Installment auctions are declined by not paying");
+
+            let price = banner.current_price;
+            let (seller_share, _, _) = Self::split_proceeds(banner_id, &owner, price);
+            let penalty = Self::percent_of(price, Self::decline_penalty_percent(), false);
+            let refund = seller_share.saturating_sub(penalty);
+
+            let free = <balances::Module<T> as Currency<_>>::free_balance(&owner);
+            ensure!(free >= refund, "The seller can't cover the refund");
+            Self::ensure_can_receive(&sender, refund)?;
+
+            Self::pay(&owner, &sender, refund)?;
+            Self::stop_leading(&sender, banner_id);
+
+            // The auction had a winner, so it doesn't relist at a price nobody bid.
+            <AutoRelist<T>>::remove(banner_id);
+            let runner_up = <PreviousBid<T>>::take(banner_id).filter(|(bidder, _)| *bidder != sender && *bidder != owner);
+            match runner_up {
+                Some((ref bidder, runner_up_price)) => {
+                    // Settling with an empty escrow leaves the banner awaiting the runner-up's
+                    // payment, exactly as for an installment win with no deposit.
+                    <BidEscrow<T>>::insert(banner_id, (bidder.clone(), Self::zero_balance()));
+                    banner.current_bidder = bidder.clone();
+                    banner.current_price = runner_up_price;
+                }
+                None => banner.has_real_bid = false,
+            }
+            Self::settle(owner, banner_id, banner)?;
+
+            Self::deposit_event(RawEvent::WinDeclined(banner_id, sender, runner_up.map(|(bidder, _)| bidder), penalty));
+
+            Ok(())
+        }
+
+        /// Pays the rest of a won installment auction, plus any buyer's fee, and takes the
+        /// banner. Must be called within `PaymentWindow` blocks of settlement.
+        fn finalize_payment(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            let pending = Self::awaiting_payment(banner_id).ok_or("This banner is not awaiting payment")?;
+            ensure!(pending.winner == sender, "Only the winning bidder can pay for this banner");
+            ensure!(<system::Module<T>>::block_number() <= pending.deadline, "The payment window has closed");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            let fee = Self::buyer_fee(pending.price);
+            let free = <balances::Module<T> as Currency<_>>::free_balance(&sender);
+            ensure!(free + pending.deposit >= pending.price + fee, "Not enough balance to pay for this banner");
+            Self::ensure_proceeds_payable(&owner, banner_id, pending.price)?;
+            Self::ensure_can_receive(&Self::fee_account(), fee)?;
+
+            // The banner changes hands before any funds move, so a currency hook that calls back
+            // in finds the sale already complete.
+            <AwaitingPayment<T>>::remove(banner_id);
+            Self::release_reserved(&sender, pending.deposit);
+            let end_height = Self::last_auction_end(banner_id).unwrap_or_else(<system::Module<T>>::block_number);
+            Self::complete_sale(owner.clone(), sender.clone(), banner_id, pending.price, end_height)?;
+
+            <balances::Module<T> as ReservableCurrency<_>>::unreserve(&sender, pending.deposit);
+            Self::pay_proceeds(&sender, &owner, banner_id, pending.price)?;
+            Self::collect_fee(&sender, fee)
+        }
+
+        /// Once a winner's payment window has passed, hands their deposit to the seller and
+        /// offers the banner to the runner-up at their own bid. Without a runner-up, the banner
+        /// stays with the seller. Anyone may call this.
+        fn forfeit_payment(origin, banner_id: T::Hash) -> Result {
+            ensure_signed(origin)?;
+
+            let pending = Self::awaiting_payment(banner_id).ok_or("This banner is not awaiting payment")?;
+            ensure!(<system::Module<T>>::block_number() > pending.deadline, "The payment window is still open");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            if !pending.deposit.is_zero() {
+                <balances::Module<T> as ReservableCurrency<_>>::repatriate_reserved(&pending.winner, &owner, pending.deposit)?;
+                Self::release_reserved(&pending.winner, pending.deposit);
+            }
+            Self::deposit_event(RawEvent::PaymentForfeited(banner_id, pending.winner.clone(), pending.deposit));
+
+            // The runner-up's own deposit was returned when they were outbid, so they have
+            // nothing to forfeit if they don't pay either.
+            match pending.runner_up.filter(|(bidder, _)| *bidder != owner) {
+                Some((runner_up, price)) => Self::await_payment(banner_id, runner_up, price, Self::zero_balance(), None),
+                None => {
+                    <AwaitingPayment<T>>::remove(banner_id);
+                    T::OnAuctionSettled::on_auction_settled(&banner_id, &owner, None, Self::zero_balance(), AuctionState::Aborted);
+                    Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Aborted));
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Lets the winner of an ended auction take the banner without waiting for a bid or
+        /// `settle_expired` to settle it.
+        fn claim(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Settleable, "This auction can't be settled yet");
+            ensure!(banner.has_real_bid && banner.current_bidder == sender, "Only the winning bidder can claim this banner");
+
+            Self::settle_auction(banner_id)?;
+
+            Ok(())
+        }
+
+        /// Hands the banner to `BurnAddress` for good. Unlike `burn_banner`, its metadata and
+        /// provenance are kept.
+        fn retire(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+
+            Self::transfer_from(sender.clone(), Self::burn_address(), banner_id)?;
+            <Retired<T>>::insert(banner_id, true);
+
+            Self::deposit_event(RawEvent::Retired(sender, banner_id));
+
+            Ok(())
+        }
+
+        fn burn_banner(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+
+            let banner = Self::banner(banner_id);
+            ensure!(!banner.can_bid, "You can't burn a banner on auction");
+            Self::ensure_not_awaiting_payment(banner_id)?;
+
+            let redeem_value = Self::redeem_value(banner_id);
+            let reward_pool = Self::reward_pool();
+            if !redeem_value.is_zero() {
+                ensure!(<balances::Module<T> as Currency<_>>::free_balance(&reward_pool) >= redeem_value,
+                    "Reward pool cannot cover the redeem value");
+                // The banner is gone once burned, so the payout must not fail afterwards.
+                Self::ensure_can_receive(&sender, redeem_value)?;
+            }
+
+            Self::burn(sender.clone(), banner_id, banner)?;
+
+            if !redeem_value.is_zero() {
+                <balances::Module<T> as Currency<_>>::transfer(&reward_pool, &sender, redeem_value)?;
+                Self::deposit_event(RawEvent::Redeemed(sender, banner_id, redeem_value));
+            }
+
+            Ok(())
+        }
+
+        /// Pushes back the end of an auction nobody has bid on yet.
+        fn extend_auction(origin, banner_id: T::Hash, extra_blocks: T::BlockNumber) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            Self::ensure_owner_or_manager(banner_id, &sender)?;
+
+            let mut banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Bidding, "This auction has ended");
+            ensure!(!banner.has_real_bid, "Can't extend an auction that has bids");
+
+            let new_end = banner.bid_end_height.checked_add(&extra_blocks).ok_or("Auction end height overflows")?;
+            Self::ensure_within_max_duration(new_end - <system::Module<T>>::block_number())?;
+
+            banner.bid_end_height = new_end;
+            <Banners<T>>::insert(banner_id, banner);
+
+            Self::deposit_event(RawEvent::AuctionExtended(banner_id, new_end));
+            Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Extended));
+
+            Ok(())
+        }
+
+        /// Changes the starting price of an auction nobody has bid on yet.
+        fn reprice_auction(origin, banner_id: T::Hash, new_price: T::Balance) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            Self::ensure_owner_or_manager(banner_id, &sender)?;
+
+            let mut banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Bidding, "This auction has ended");
+            ensure!(!banner.has_real_bid, "Can't reprice an auction that has bids");
+            let (min_bid, max_bid) = Self::bid_limits(banner_id);
+            Self::ensure_valid_bid_limits(new_price, min_bid, max_bid)?;
+
+            banner.current_price = new_price;
+            <Banners<T>>::insert(banner_id, banner);
+
+            Self::deposit_event(RawEvent::AuctionRepriced(banner_id, new_price));
+
+            Ok(())
+        }
+
+        /// Aborts an auction nobody has bid on yet. The banner stays with its owner.
+        fn cancel_auction(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::ensure_owner_or_manager(banner_id, &sender)?;
+
+            let banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(!banner.has_real_bid, "Can't cancel an auction that has bids");
+
+            // A cancelled auction shouldn't come straight back.
+            <AutoRelist<T>>::remove(banner_id);
+            Self::settle(owner.clone(), banner_id, banner)?;
+            Self::deposit_event(RawEvent::CancelAuction(owner, banner_id));
+
+            Ok(())
+        }
+
+        /// Ends a live auction early, selling the banner to its high bidder at their bid.
+        fn accept_highest_bid(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::ensure_owner_or_manager(banner_id, &sender)?;
+            Self::ensure_not_blocked(&owner)?;
+
+            let banner = Self::banner(banner_id);
+            ensure!(banner.can_bid, "This banner is not on auction");
+            ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Bidding, "This auction has ended");
+            ensure!(banner.has_real_bid, "This auction has no bids to accept");
+
+            Self::settle(owner, banner_id, banner)?;
+
+            Ok(())
+        }
+
+        /// Lists a banner for anyone to buy outright at `price`.
+        fn list_fixed_price(origin, banner_id: T::Hash, price: T::Balance) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+            ensure!(!Self::banner(banner_id).can_bid, "This banner is on auction");
+            Self::ensure_not_awaiting_payment(banner_id)?;
+
+            <FixedPrice<T>>::insert(banner_id, price);
+
+            Self::deposit_event(RawEvent::Listed(sender, banner_id, price));
+
+            Ok(())
+        }
+
+        /// Buys a listed banner at its asking price, paid out like a winning bid.
+        fn buy_fixed(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let price = Self::fixed_price(banner_id).ok_or("This banner is not listed for sale")?;
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner != sender, "You can't buy your own banner");
+
+            let fee = Self::buyer_fee(price);
+            let free = <balances::Module<T> as Currency<_>>::free_balance(&sender);
+            ensure!(free >= price + fee, "Not enough balance to buy this banner");
+
+            // As in `bid`, check every payout before the first transfer is made.
+            Self::ensure_proceeds_payable(&owner, banner_id, price)?;
+            Self::ensure_can_receive(&Self::fee_account(), fee)?;
+
+            Self::pay_proceeds(&sender, &owner, banner_id, price)?;
+            Self::collect_fee(&sender, fee)?;
+            Self::transfer_from(owner, sender.clone(), banner_id)?;
+            Self::record_sale(banner_id, price);
+
+            Self::deposit_event(RawEvent::Sold(sender, banner_id, price));
+
+            Ok(())
+        }
+
+        fn delist(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<FixedPrice<T>>::exists(banner_id), "This banner is not listed for sale");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+
+            <FixedPrice<T>>::remove(banner_id);
+
+            Self::deposit_event(RawEvent::Delisted(sender, banner_id));
+
+            Ok(())
+        }
+
+        /// Offers to buy a banner that isn't on auction, reserving `amount` until the owner
+        /// accepts or the buyer withdraws.
+        fn make_offer(origin, banner_id: T::Hash, amount: T::Balance) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner != sender, "You can't make an offer on your own banner");
+            ensure!(!Self::banner(banner_id).can_bid, "This banner is on auction");
+            ensure!(!amount.is_zero(), "Offer amount must be greater than zero");
+            ensure!(!<Offers<T>>::exists((banner_id, sender.clone())), "You already have an offer on this banner");
+
+            let offer_count = Self::offer_count(banner_id);
+            let max_offers = Self::max_offers_per_banner();
+            ensure!(max_offers == 0 || offer_count < max_offers, "This banner has too many outstanding offers");
+            let new_offer_count = Self::increment(offer_count, "Overflow adding an offer to this banner")?;
+
+            <balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, amount)?;
+            <Offers<T>>::insert((banner_id, sender.clone()), amount);
+            <OfferCount<T>>::insert(banner_id, new_offer_count);
+            <ReservedByBidder<T>>::mutate(&sender, |reserved| *reserved = reserved.saturating_add(amount));
+
+            Self::deposit_event(RawEvent::OfferMade(sender, banner_id, amount));
+
+            Ok(())
+        }
+
+        /// Sells the banner to `buyer` for the amount they offered.
+        fn accept_offer(origin, banner_id: T::Hash, buyer: T::AccountId) -> Result {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_not_blocked(&sender)?;
+            Self::ensure_not_blocked(&buyer)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+            ensure!(owner == sender, "You do not own this banner");
+            ensure!(!Self::banner(banner_id).can_bid, "This banner is on auction");
+            ensure!(buyer != owner, "You can't accept your own offer");
+            Self::ensure_not_awaiting_payment(banner_id)?;
+            ensure!(<Offers<T>>::exists((banner_id, buyer.clone())), "No offer from this buyer");
+
+            // Checked before repatriating: a partial repatriation would leave the offer recorded
+            // at more than is still reserved for it.
+            let amount = Self::offer((banner_id, buyer.clone()));
+            ensure!(<balances::Module<T> as ReservableCurrency<_>>::reserved_balance(&buyer) >= amount,
+                "Buyer's reserved balance does not cover the offer");
+            let fee = Self::buyer_fee(amount);
+            let free = <balances::Module<T> as Currency<_>>::free_balance(&buyer);
+            ensure!(free >= fee, "Buyer can't cover the buyer's fee");
+            Self::ensure_proceeds_payable(&owner, banner_id, amount)?;
+            Self::ensure_can_receive(&Self::fee_account(), fee)?;
+
+            Self::repatriate_proceeds(&buyer, &owner, banner_id, amount)?;
+            Self::collect_fee(&buyer, fee)?;
+            <Offers<T>>::remove((banner_id, buyer.clone()));
+            <OfferCount<T>>::mutate(banner_id, |n| *n = n.saturating_sub(1));
+            Self::release_reserved(&buyer, amount);
+
+            Self::transfer_from(owner.clone(), buyer.clone(), banner_id)?;
+            Self::record_sale(banner_id, amount);
+
+            Self::deposit_event(RawEvent::OfferAccepted(owner, buyer, banner_id, amount));
+
+            Ok(())
+        }
+
+        fn withdraw_offer(origin, banner_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Offers<T>>::exists((banner_id, sender.clone())), "You have no offer on this banner");
+
+            let amount = <Offers<T>>::take((banner_id, sender.clone()));
+            <OfferCount<T>>::mutate(banner_id, |n| *n = n.saturating_sub(1));
+            <balances::Module<T> as ReservableCurrency<_>>::unreserve(&sender, amount);
+            Self::release_reserved(&sender, amount);
+
+            Self::deposit_event(RawEvent::OfferWithdrawn(sender, banner_id));
+
+            Ok(())
+        }
+
+        fn bid(origin, banner_id: T::Hash, bid_price: T::Balance) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+            let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+
+            let mut banner = Self::banner(banner_id);
+            if !banner.can_bid {
+                return Err(if Self::was_auctioned(banner_id) { "auction has ended" } else { "banner is not on auction" });
+            }
+
+            let phase = Self::auction_phase(banner.bid_end_height);
+            ensure!(phase != AuctionPhase::AwaitingSettlement, "This auction has ended and is awaiting settlement");
+
+            if phase == AuctionPhase::Bidding {
+                // still can bid this banner
+                ensure!(owner != sender, "You can't bid your own banner");
+                Self::ensure_not_blocked(&sender)?;
+
+                let allowed_bidders = Self::allowed_bidders(banner_id);
+                ensure!(allowed_bidders.is_empty() || allowed_bidders.contains(&sender), "not invited to this auction");
+                ensure!(bid_price > banner.current_price, "your bid price must be greater than current price");
+                ensure!(bid_price >= Self::min_next_bid(banner.current_price), "Bid is below the minimum increment");
+
+                let (min_bid, max_bid) = Self::bid_limits(banner_id);
+                ensure!(min_bid.map_or(true, |min_bid| bid_price >= min_bid), "Bid is below this auction's minimum bid");
+                ensure!(max_bid.map_or(true, |max_bid| bid_price <= max_bid), "Bid is above this auction's maximum bid");
+
+                let high_value_threshold = Self::high_value_threshold();
+                if !high_value_threshold.is_zero() && bid_price > high_value_threshold {
+                    ensure!(T::AttestationCheck::is_attested(&sender), "High-value bids require an attestation");
+                }
+
+                let now = <system::Module<T>>::block_number();
+                let bid_cooldown = Self::bid_cooldown();
+                if !bid_cooldown.is_zero() {
+                    let last_bid = <LastBidBlock<T>>::get(&banner_id, &sender);
+                    ensure!(last_bid.map_or(true, |last| now >= last.saturating_add(bid_cooldown)), "You are bidding too soon after your last bid");
+                }
+
+                // An installment bid only reserves its deposit now, less whatever the bidder
+                // already has reserved on this auction; the buyer's fee is paid with the rest.
+                let escrow = Self::installment_escrow(bid_price);
+                let held = Self::bid_escrow(banner_id)
+                    .filter(|(holder, _)| *holder == sender)
+                    .map_or_else(Self::zero_balance, |(_, held)| held);
+                let due_now = escrow.map_or(bid_price, |escrow| escrow.saturating_sub(held));
+
+                let bid_deposit = Self::bid_deposit();
+                let buyer_fee = if escrow.is_some() { Self::zero_balance() } else { Self::buyer_fee(bid_price) };
+                // A buyer's fee is owed on the whole price. The previous high bidder already paid
+                // theirs, so it is refunded along with their bid and only the difference is new.
+                let previous_fee = if escrow.is_some() || !banner.has_real_bid { Self::zero_balance() } else { Self::buyer_fee(banner.current_price) };
+                let fee = buyer_fee - previous_fee;
+                let raise = if !banner.has_real_bid { bid_price } else { bid_price - banner.current_price };
+                // A high bidder raising their own bid owes only the raise; the refund would be
+                // paid to themselves.
+                let raising_own_bid = escrow.is_none() && banner.has_real_bid && banner.current_bidder == sender;
+                let cost = if raising_own_bid { raise + fee } else { due_now + buyer_fee } + bid_deposit;
+
+                // Nothing below can be rolled back, so every check comes before the first transfer.
+                let free = <balances::Module<T> as Currency<_>>::free_balance(&sender);
+                ensure!(free >= cost, "Not enough balance to cover the bid");
+                if Self::require_live_bidders() {
+                    // The bidder becomes the banner owner at settlement, so don't let the bid
+                    // itself reap their account.
+                    let existential_deposit = <balances::Module<T> as Currency<_>>::minimum_balance();
+                    ensure!(free - cost >= existential_deposit, "bid would leave bidder below existential deposit");
+                }
+
+                let mut leading = Self::bids_by_account(&sender);
+                if !leading.contains(&banner_id) {
+                    Self::bounded_push(&mut leading, banner_id, MAX_LEADING_BIDS, "You are already the high bidder on too many auctions")?;
+                }
+
+                if escrow.is_none() {
+                    Self::ensure_proceeds_payable(&owner, banner_id, raise)?;
+                    if banner.has_real_bid {
+                        Self::ensure_can_receive(&banner.current_bidder, banner.current_price + previous_fee)?;
+                    }
+                }
+                Self::ensure_can_receive(&Self::fee_account(), fee + bid_deposit)?;
+
+                Self::pay(&sender, &Self::fee_account(), bid_deposit)?;
+                if let Some(escrow) = escrow {
+                    Self::escrow_bid(&sender, banner_id, escrow)?;
+                } else {
+                    if !banner.has_real_bid {
+                        Self::pay_proceeds(&sender, &owner, banner_id, bid_price)?;
+                    } else {
+                        // Refund the previous high bidder; only the raise is new sale proceeds. A
+                        // high bidder raising their own bid has nothing to refund.
+                        Self::pay(&sender, &banner.current_bidder, banner.current_price + previous_fee)?;
+                        Self::pay_proceeds(&sender, &owner, banner_id, raise)?;
+                    }
+                    Self::collect_fee(&sender, fee)?;
+                }
+                if banner.has_real_bid && banner.current_bidder != sender {
+                    <PreviousBid<T>>::insert(banner_id, (banner.current_bidder.clone(), banner.current_price));
+                    Self::stop_leading(&banner.current_bidder, banner_id);
+                }
+                <BidsByAccount<T>>::insert(&sender, leading);
+
+                banner.current_bidder = sender.clone();
+                banner.current_price = bid_price;
+                banner.has_real_bid = true;
+                let blocks_left = banner.bid_end_height - now;
+
+                <Banners<T>>::insert(banner_id, banner);
+                <BidCount<T>>::mutate(banner_id, |n| *n = n.saturating_add(1));
+                if !bid_cooldown.is_zero() {
+                    <LastBidBlock<T>>::insert(&banner_id, &sender, now);
+                }
+
+                Self::deposit_event(RawEvent::Bid(sender.clone(), banner_id, bid_price));
+                Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Bid));
+
+                let anti_snipe_window = Self::anti_snipe_window();
+                if !anti_snipe_window.is_zero() && blocks_left <= anti_snipe_window {
+                    Self::deposit_event(RawEvent::LateBid(banner_id, sender, blocks_left));
+                }
+
+            }else {
+                Self::settle_auction(banner_id)?;
+            }
+
+            Ok(())
+        }
+
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Whether `name` could be used for a new banner: it must pass the same checks as
+    /// `create_banner` and not belong to an existing banner.
+    pub fn is_name_available(name: Vec<u8>) -> bool {
+        Self::validate_name(&name).is_ok() && !<BannerByName<T>>::exists(Self::name_key(&name))
+    }
+
+    /// `(all_banners_count, distinct_owner_count)`, both maintained in storage.
+    pub fn collection_stats() -> (u64, u64) {
+        (Self::all_banners_count(), Self::distinct_owner_count())
+    }
+
+    /// Resolves `banner_of_owner_by_index` and loads the banner in one go.
+    pub fn owned_banner_at(owner: T::AccountId, index: u64) -> Option<Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>> {
+        if index >= Self::owned_banner_count(&owner) {
+            return None;
+        }
+
+        Some(Self::banner(Self::banner_of_owner_by_index((owner, index))))
+    }
+
+    /// The account holding the highest bid on `banner_id`. While nobody has bid, the auction
+    /// records the owner as its bidder; that placeholder is reported as `None`.
+    pub fn current_bidder_of(banner_id: T::Hash) -> Option<T::AccountId> {
+        let banner = Self::banner(banner_id);
+        if !banner.can_bid || !banner.has_real_bid {
+            return None;
+        }
+
+        Some(banner.current_bidder)
+    }
+
+    /// Previews how a sale would be paid out without touching state. Unknown banners report
+    /// the whole price as the seller's. Under `FeePayer::Buyer` the commission is paid on top
+    /// of `sale_price`, so the three legs add up to more than it.
+    pub fn estimate_proceeds(banner_id: T::Hash, sale_price: T::Balance) -> (T::Balance, T::Balance, T::Balance) {
+        let (seller, royalty, commission) = match Self::owner_of(banner_id) {
+            Some(owner) => Self::split_proceeds(banner_id, &owner, sale_price),
+            None => (sale_price, Self::zero_balance(), Self::zero_balance()),
+        };
+
+        (seller, royalty, commission + Self::buyer_fee(sale_price))
+    }
+
+    pub fn auction_status(banner_id: T::Hash) -> Option<AuctionStatus<T::AccountId, T::Balance, T::BlockNumber>> {
+        let banner = Self::banner(banner_id);
+        if !banner.can_bid {
+            return None;
+        }
+
+        let previous_bid = Self::previous_bid(banner_id);
+        let blocks_remaining = Self::blocks_remaining(banner.bid_end_height, <system::Module<T>>::block_number());
+        Some(AuctionStatus {
+            current_price: banner.current_price,
+            current_bidder: Self::current_bidder_of(banner_id),
+            bid_end_height: banner.bid_end_height,
+            seconds_remaining: Self::blocks_to_u64(blocks_remaining).saturating_mul(Self::seconds_per_block()),
+            previous_bidder: previous_bid.as_ref().map(|(bidder, _)| bidder.clone()),
+            previous_price: previous_bid.map_or_else(Self::zero_balance, |(_, price)| price),
+            listing: Self::listing_metadata(banner_id),
+        })
+    }
+
+    /// Number of banners as of the latest sample taken at or before `block`, or `None` if that
+    /// sample hasn't been taken yet or has been pruned.
+    pub fn supply_at(block: T::BlockNumber) -> Option<u64> {
+        let interval = Self::supply_sample_interval();
+        if interval.is_zero() {
+            return None;
+        }
+        Self::supply_history(block - block % interval)
+    }
+
+    pub fn suggested_reserve(banner_id: T::Hash) -> Option<T::Balance> {
+        T::PriceOracle::suggested_reserve(&banner_id)
+    }
+
+    /// Live auctions with at least one bid, ordered by `BidCount`; ties come in no particular
+    /// order. At most `MAX_TOP_BANNERS` entries are returned whatever `limit` asks for.
+    pub fn top_banners_by_bids(limit: u32) -> Vec<(T::Hash, u32)> {
+        let mut top = Vec::new();
+
+        for index in 0..Self::active_auctions_count() {
+            let banner_id = Self::active_auction_by_index(index);
+            let bid_count = Self::bid_count(banner_id);
+            if bid_count > 0 {
+                top.push((banner_id, bid_count));
+            }
+        }
+
+        top.sort_by(|a, b| b.1.cmp(&a.1));
+        top.truncate(rstd::cmp::min(limit as usize, MAX_TOP_BANNERS));
+        top
+    }
+
+    /// The chain of custody of `banner_id`, from the mint (or the oldest record kept) onwards.
+    pub fn provenance(banner_id: T::Hash) -> Vec<(T::AccountId, T::BlockNumber)> {
+        Self::ownership_history(banner_id)
+    }
+
+    /// Funds `who` currently has locked in this module: their outstanding offers, listing
+    /// deposits, and the deposits on installment auctions they lead or have won but not paid
+    /// for. Other bids are paid out as they are placed.
+    pub fn reserved_of(who: T::AccountId) -> T::Balance {
+        Self::reserved_by_bidder(who)
+    }
+
+    pub fn bids_of(who: T::AccountId) -> Vec<T::Hash> {
+        Self::bids_by_account(who)
+    }
+
+    /// The lowest bid accepted over `current_price` under `BidIncrementTiers`.
+    pub fn min_next_bid(current_price: T::Balance) -> T::Balance {
+        let increment = Self::bid_increment_tiers().into_iter()
+            .take_while(|(threshold, _)| *threshold <= current_price)
+            .last()
+            .map_or_else(Self::zero_balance, |(_, increment)| increment);
+
+        current_price + increment
+    }
+
+    /// Up to `limit` live auctions starting at position `start` of `ActiveAuctionsArray`,
+    /// capped at `MAX_AUCTION_PAGE`. Settling an auction can reorder the set between pages.
+    pub fn active_auctions_detailed(start: u64, limit: u64) -> Vec<AuctionView<T::Hash, T::AccountId, T::Balance, T::BlockNumber>> {
+        let now = <system::Module<T>>::block_number();
+        let end = start.saturating_add(limit.min(MAX_AUCTION_PAGE)).min(Self::active_auctions_count());
+
+        (start..end).map(|index| {
+            let banner_id = Self::active_auction_by_index(index);
+            let banner = Self::banner(banner_id);
+            AuctionView {
+                banner_id,
+                current_price: banner.current_price,
+                current_bidder: Self::current_bidder_of(banner_id),
+                bid_end_height: banner.bid_end_height,
+                blocks_remaining: Self::blocks_remaining(banner.bid_end_height, now),
+            }
+        }).collect()
+    }
+
+    pub fn banner_detail(banner_id: T::Hash) -> Option<BannerDetail<T::Hash, T::Balance, T::AccountId, T::BlockNumber>> {
+        let owner = Self::owner_of(banner_id)?;
+        let banner = Self::banner(banner_id);
+        let blocks_remaining = if banner.can_bid {
+            Self::blocks_remaining(banner.bid_end_height, <system::Module<T>>::block_number())
+        } else {
+            Self::blocks(0)
+        };
+
+        Some(BannerDetail {
+            banner,
+            owner,
+            auction: Self::auction_status(banner_id),
+            blocks_remaining,
+            last_sale_price: Self::last_sale_price(banner_id),
+            verified: Self::is_verified(banner_id),
+        })
+    }
+
+    pub fn localized_metadata(banner_id: T::Hash, lang: [u8; 2]) -> (Vec<u8>, Vec<u8>) {
+        <LocalizedMetadata<T>>::get((banner_id, lang)).unwrap_or_else(|| {
+            let banner = Self::banner(banner_id);
+            (banner.name, banner.desc)
+        })
+    }
+
+    pub fn banners_by_ids(ids: Vec<T::Hash>) -> Vec<(T::Hash, Option<Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>>)> {
+        ids.into_iter().take(MAX_BANNERS_BY_IDS).map(|banner_id| {
+            let banner = if <Banners<T>>::exists(banner_id) { Some(Self::banner(banner_id)) } else { None };
+            (banner_id, banner)
+        }).collect()
+    }
+
+    /// Up to `limit` banners (at most `MAX_BANNER_PAGE`) following `cursor`, starting from the
+    /// first with `None`. The returned cursor is `None` once the last banner has been returned.
+    /// New banners are appended, so minting between pages never repeats or skips an entry.
+    pub fn banners_page(cursor: Option<u64>, limit: u64) -> (Vec<T::Hash>, Option<u64>) {
+        Self::page(cursor, limit, Self::all_banners_count(), Self::banner_by_index)
+    }
+
+    /// `banners_page` over the banners `owner` holds. Banners `owner` receives between pages
+    /// are appended; one leaving their collection moves their last banner into its slot.
+    pub fn banners_of(owner: T::AccountId, cursor: Option<u64>, limit: u64) -> (Vec<T::Hash>, Option<u64>) {
+        Self::page(cursor, limit, Self::owned_banner_count(&owner), |index| Self::banner_of_owner_by_index((owner.clone(), index)))
+    }
+
+    fn page<F: Fn(u64) -> T::Hash>(cursor: Option<u64>, limit: u64, count: u64, at: F) -> (Vec<T::Hash>, Option<u64>) {
+        let start = cursor.unwrap_or(0);
+        let end = start.saturating_add(limit.min(MAX_BANNER_PAGE)).min(count);
+        let ids = (start..end).map(at).collect();
+        (ids, if end < count { Some(end) } else { None })
+    }
+
+    /// Banners whose image is `url`. Reusing an image is allowed, so this only lets UIs warn
+    /// about it.
+    pub fn banners_with_image(url: Vec<u8>) -> Vec<T::Hash> {
+        Self::image_url_index(url)
+    }
+
+    fn index_image_url(url: &[u8], banner_id: T::Hash) {
+        let mut banner_ids = Self::image_url_index(url.to_vec());
+        if Self::bounded_push(&mut banner_ids, banner_id, MAX_BANNERS_PER_IMAGE, "Too many banners share this image").is_ok() {
+            <ImageUrlIndex<T>>::insert(url.to_vec(), banner_ids);
+        }
+    }
+
+    fn unindex_image_url(url: &[u8], banner_id: T::Hash) {
+        let mut banner_ids = Self::image_url_index(url.to_vec());
+        banner_ids.retain(|id| *id != banner_id);
+        if banner_ids.is_empty() {
+            <ImageUrlIndex<T>>::remove(url.to_vec());
+        } else {
+            <ImageUrlIndex<T>>::insert(url.to_vec(), banner_ids);
+        }
+    }
+
+    fn blocks_remaining(bid_end_height: T::BlockNumber, now: T::BlockNumber) -> T::BlockNumber {
+        if now < bid_end_height { bid_end_height - now } else { Self::blocks(0) }
+    }
+
+    /// Every check `create_banner` makes on its metadata, in the same order, without touching
+    /// state.
+    pub fn validate_metadata(name: &[u8], url: &[u8], desc: &[u8]) -> Result {
+        Self::validate_name(name)?;
+        ensure!(!<BannerByName<T>>::exists(Self::name_key(name)), "This banner name is already taken");
+        Self::validate_url(url)?;
+        Self::validate_desc(desc)
+    }
+
+    fn create(owner: T::AccountId, name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>, content_hash: T::Hash) -> rstd::result::Result<T::Hash, &'static str> {
+        Self::validate_metadata(&name, &url, &desc)?;
+
+        let nonce = <Nonce<T>>::get();
+        let new_nonce = Self::increment(nonce, "Nonce overflow")?;
+        let random_hash = (<system::Module<T>>::random_seed(), &owner, nonce)
+            .using_encoded(<T as system::Trait>::Hashing::hash);
+
+        let new_banner = Banner {
+            id: random_hash,
+            name: name,
+            image_url: url,
+            content_hash,
+            desc: desc,
+            current_price: Self::zero_balance(),
+            current_bidder:  owner.clone(),
+            bid_end_height: Self::blocks(0),
+            can_bid: false,
+            has_real_bid: false,
+            royalty_locked: false,
+        };
+
+        Self::mint(owner, random_hash, new_banner)?;
+
+        <Nonce<T>>::put(new_nonce);
+
+        Ok(random_hash)
+    }
+
+    /// Splits `amount` paid for `banner_id` into `(seller, royalty, commission)`. The creator
+    /// only earns a royalty when someone else is selling. Commission is only deducted here
+    /// when the seller pays it; otherwise see `buyer_fee`.
+    fn split_proceeds(banner_id: T::Hash, seller: &T::AccountId, amount: T::Balance) -> (T::Balance, T::Balance, T::Balance) {
+        let round_up = Self::rounding_policy() == RoundingPolicy::FavorFees;
+        let commission = match Self::fee_payer() {
+            FeePayer::Seller => Self::percent_of(amount, Self::commission_percent(), round_up),
+            FeePayer::Buyer => Self::zero_balance(),
+        };
+        let royalty = match Self::creator_of(banner_id) {
+            Some(ref creator) if creator != seller => Self::percent_of(amount, Self::capped_royalty_percent(banner_id), round_up).min(amount - commission),
+            _ => Self::zero_balance(),
+        };
+
+        // A seller who is also the fee account keeps the commission as part of their share,
+        // so it arrives in a single transfer.
+        if *seller == Self::fee_account() {
+            return (amount - royalty, royalty, Self::zero_balance());
+        }
+
+        (amount - commission - royalty, royalty, commission)
+    }
+
+    /// Pays `amount` of sale proceeds from `buyer`, split between the seller, the creator and
+    /// the fee account.
+    fn pay_proceeds(buyer: &T::AccountId, seller: &T::AccountId, banner_id: T::Hash, amount: T::Balance) -> Result {
+        Self::distribute_proceeds(buyer, seller, banner_id, amount, Self::pay)
+    }
+
+    /// Like `pay_proceeds`, but out of `amount` the buyer already has reserved, as for an offer.
+    fn repatriate_proceeds(buyer: &T::AccountId, seller: &T::AccountId, banner_id: T::Hash, amount: T::Balance) -> Result {
+        Self::distribute_proceeds(buyer, seller, banner_id, amount, Self::repatriate)
+    }
+
+    fn distribute_proceeds(
+        buyer: &T::AccountId,
+        seller: &T::AccountId,
+        banner_id: T::Hash,
+        amount: T::Balance,
+        transfer: fn(&T::AccountId, &T::AccountId, T::Balance) -> Result,
+    ) -> Result {
+        let (seller_share, royalty, commission) = Self::split_proceeds(banner_id, seller, amount);
+        let pays_royalty = Self::creator_of(banner_id).map_or(false, |creator| &creator != seller);
+        if pays_royalty && Self::capped_royalty_percent(banner_id) < Self::royalty_percent_of(banner_id) {
+            Self::deposit_event(RawEvent::RoyaltyCapped(banner_id, Self::capped_royalty_percent(banner_id)));
+        }
+
+        transfer(buyer, seller, seller_share)?;
+        if !royalty.is_zero() {
+            let creator = Self::creator_of(banner_id).ok_or("No creator for this banner")?;
+            transfer(buyer, &creator, royalty)?;
+            <TotalRoyaltiesPaid<T>>::mutate(|total| *total = total.saturating_add(royalty));
+        }
+        transfer(buyer, &Self::fee_account(), commission)?;
+        <TotalFeesCollected<T>>::mutate(|total| *total = total.saturating_add(commission));
+        Ok(())
+    }
+
+    /// Pays `amount` of commission to the fee account and adds it to `TotalFeesCollected`.
+    fn collect_fee(from: &T::AccountId, amount: T::Balance) -> Result {
+        Self::pay(from, &Self::fee_account(), amount)?;
+        <TotalFeesCollected<T>>::mutate(|total| *total = total.saturating_add(amount));
+        Ok(())
+    }
+
+    /// Transfers `amount` unless there is nothing to move: a zero amount, or a buyer who is
+    /// also the recipient, e.g. the fee account or the creator buying.
+    fn pay(from: &T::AccountId, to: &T::AccountId, amount: T::Balance) -> Result {
+        if amount.is_zero() || from == to {
+            return Ok(());
+        }
+        <balances::Module<T> as Currency<_>>::transfer(from, to, amount)
+    }
+
+    /// Moves `amount` of `from`'s reserved balance to `to`, who may be `from` itself, e.g. a
+    /// creator buying back their own banner.
+    fn repatriate(from: &T::AccountId, to: &T::AccountId, amount: T::Balance) -> Result {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        if from == to {
+            <balances::Module<T> as ReservableCurrency<_>>::unreserve(from, amount);
+            return Ok(());
+        }
+        <balances::Module<T> as ReservableCurrency<_>>::repatriate_reserved(from, to, amount)
+    }
+
+    /// Checks that each share of `amount` could be paid out by `pay_proceeds`.
+    fn ensure_proceeds_payable(seller: &T::AccountId, banner_id: T::Hash, amount: T::Balance) -> Result {
+        let (seller_share, royalty, commission) = Self::split_proceeds(banner_id, seller, amount);
+
+        Self::ensure_can_receive(seller, seller_share)?;
+        if !royalty.is_zero() {
+            let creator = Self::creator_of(banner_id).ok_or("No creator for this banner")?;
+            Self::ensure_can_receive(&creator, royalty)?;
+        }
+        Self::ensure_can_receive(&Self::fee_account(), commission)
+    }
+
+    fn stop_leading(bidder: &T::AccountId, banner_id: T::Hash) {
+        <BidsByAccount<T>>::mutate(bidder, |leading| leading.retain(|id| *id != banner_id));
+    }
+
+    fn release_reserved(who: &T::AccountId, amount: T::Balance) {
+        <ReservedByBidder<T>>::mutate(who, |reserved| *reserved = reserved.checked_sub(&amount).unwrap_or_else(Zero::zero));
+    }
+
+    /// The creator's own royalty if they set one, otherwise the chain-wide `RoyaltyPercent`.
+    fn royalty_percent_of(banner_id: T::Hash) -> u32 {
+        Self::banner_royalty(banner_id).unwrap_or_else(Self::royalty_percent)
+    }
+
+    fn capped_royalty_percent(banner_id: T::Hash) -> u32 {
+        Self::royalty_percent_of(banner_id).min(Self::max_royalty_percent())
+    }
+
+    /// Bookkeeping shared by every completed sale.
+    fn record_sale(banner_id: T::Hash, price: T::Balance) {
+        <LastSalePrice<T>>::insert(banner_id, price);
+        <Banners<T>>::mutate(banner_id, |banner| banner.royalty_locked = true);
+    }
+
+    /// Commission the buyer owes on top of `price`. Always zero when the seller pays it.
+    fn buyer_fee(price: T::Balance) -> T::Balance {
+        match Self::fee_payer() {
+            FeePayer::Seller => Self::zero_balance(),
+            FeePayer::Buyer => Self::percent_of(price, Self::commission_percent(), Self::rounding_policy() == RoundingPolicy::FavorFees),
+        }
+    }
+
+    /// A transfer into a reaped account has to recreate it, which needs at least the
+    /// existential deposit.
+    fn ensure_can_receive(who: &T::AccountId, amount: T::Balance) -> Result {
+        let existential_deposit = <balances::Module<T> as Currency<_>>::minimum_balance();
+        let is_dead = <balances::Module<T> as Currency<_>>::total_balance(who).is_zero();
+        ensure!(amount.is_zero() || !is_dead || amount >= existential_deposit,
+            "payment is below the existential deposit needed to recreate the recipient's account");
+        Ok(())
+    }
+
+    /// `percent`% of `amount`, rounded down or up. Percentages above 100 count as 100.
+    fn percent_of(amount: T::Balance, percent: u32, round_up: bool) -> T::Balance {
+        let percent = <T::Balance as As<u64>>::sa(percent.min(100) as u64);
+        let hundred = <T::Balance as As<u64>>::sa(100);
+        let fraction = amount % hundred * percent;
+        let share = amount / hundred * percent + fraction / hundred;
+
+        if round_up && !(fraction % hundred).is_zero() {
+            share + <T::Balance as As<u64>>::sa(1)
+        } else {
+            share
+        }
+    }
+
+    /// Supply, ownership, offer and nonce counters are bumped through `increment` and
+    /// `decrement`, so a counter at its limit fails the call with `err` instead of wrapping or
+    /// panicking. Auction tallies (`BidCount`, `AuctionCount`, `ActiveAuctionCount` and
+    /// `ActiveAuctionsCount`) saturate instead: `start_auction` and settlement can't fail
+    /// halfway, and a `u32` or `u64` of bids or auctions is out of reach.
+    fn increment<N: CheckedAdd + One>(n: N, err: &'static str) -> rstd::result::Result<N, &'static str> {
+        n.checked_add(&N::one()).ok_or(err)
+    }
+
+    fn decrement<N: CheckedSub + One>(n: N, err: &'static str) -> rstd::result::Result<N, &'static str> {
+        n.checked_sub(&N::one()).ok_or(err)
+    }
+
+    fn zero_balance() -> T::Balance {
+        Zero::zero()
+    }
+
+    /// `n` blocks as a `T::BlockNumber`. All block-count conversions go through here so they
+    /// only need updating in one place if `As` goes away.
+    fn blocks(n: u64) -> T::BlockNumber {
+        <T::BlockNumber as As<u64>>::sa(n)
+    }
+
+    /// The inverse of `blocks`.
+    fn blocks_to_u64(n: T::BlockNumber) -> u64 {
+        <T::BlockNumber as As<u64>>::as_(n)
+    }
+
+    fn validate_name(name: &[u8]) -> Result {
+        ensure!(!name.is_empty(), "Banner name must not be empty");
+        ensure!(name.len() <= MAX_NAME_LENGTH, "Banner name is too long");
+        Ok(())
+    }
+
+    fn validate_desc(desc: &[u8]) -> Result {
+        ensure!(desc.len() <= MAX_DESC_LENGTH, "Banner description is too long");
+        Ok(())
+    }
+
+    /// The `BannerByName` key for `name`. With `CaseInsensitiveNames` set, ASCII letters are
+    /// lowercased; every other byte, including non-ASCII UTF-8, is kept as is.
+    fn name_key(name: &[u8]) -> Vec<u8> {
+        if Self::case_insensitive_names() {
+            name.iter().map(|c| c.to_ascii_lowercase()).collect()
+        } else {
+            name.to_vec()
+        }
+    }
+
+    /// Rejects urls whose scheme isn't in `AllowedUrlSchemes`, e.g. `javascript:` or `data:`.
+    fn validate_url(url: &[u8]) -> Result {
+        ensure!(url.len() <= MAX_URL_LENGTH, "Image url is too long");
+        let schemes = Self::allowed_url_schemes();
+        ensure!(schemes.is_empty() || schemes.iter().any(|scheme| url.starts_with(scheme)), "unsupported url scheme");
+        Ok(())
+    }
+
+    /// Live auctions where `who` is either the seller or the current high bidder, along with
+    /// the height at which each one ends. At most `MAX_ENDING_AUCTIONS` entries are returned.
+    pub fn auctions_ending_for(who: T::AccountId) -> Vec<(T::Hash, T::BlockNumber)> {
+        let mut ending = Vec::new();
+
+        for index in 0..Self::active_auctions_count() {
+            if ending.len() >= MAX_ENDING_AUCTIONS {
+                return ending;
+            }
+
+            let banner_id = Self::active_auction_by_index(index);
+            if Self::owner_of(banner_id).map_or(false, |owner| owner == who) {
+                ending.push((banner_id, Self::banner(banner_id).bid_end_height));
+            }
+        }
+
+        for banner_id in Self::bids_by_account(&who) {
+            if ending.len() >= MAX_ENDING_AUCTIONS {
+                break;
+            }
+            if !ending.iter().any(|&(id, _)| id == banner_id) {
+                ending.push((banner_id, Self::banner(banner_id).bid_end_height));
+            }
+        }
+
+        ending
+    }
+
+    fn mint(to: T::AccountId, banner_id: T::Hash, new_banner: Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>) -> Result {
+        ensure!(!<BannerOwner<T>>::exists(banner_id), "banner already exists");
+
+        let owned_banner_count = Self::owned_banner_count(&to);
+
+        let new_owned_banner_count = Self::increment(owned_banner_count, "Overflow adding a new banner to account balance")?;
+
+        let all_banners_count = Self::all_banners_count();
+
+        let new_all_banners_count = Self::increment(all_banners_count, "Overflow adding a new banner to total supply")?;
+
+        let mut distinct_owner_count = Self::distinct_owner_count();
+        if owned_banner_count == 0 {
+            distinct_owner_count = Self::increment(distinct_owner_count, "Overflow adding a new banner owner")?;
+        }
+
+        let new_created_count = Self::increment(Self::created_count(&to), "Overflow counting banners created by account")?;
+
+        <BannerByName<T>>::insert(Self::name_key(&new_banner.name), banner_id);
+        Self::index_image_url(&new_banner.image_url, banner_id);
+        <Banners<T>>::insert(banner_id, new_banner);
+        <BannerOwner<T>>::insert(banner_id, &to);
+        <Creator<T>>::insert(banner_id, &to);
+        <CreatedCount<T>>::insert(&to, new_created_count);
+        Self::record_owner(banner_id, to.clone());
+
+        <AllBannersArray<T>>::insert(all_banners_count, banner_id);
+        <AllBannersCount<T>>::put(new_all_banners_count);
+        <AllBannersIndex<T>>::insert(banner_id, all_banners_count);
+
+        <OwnedBannersArray<T>>::insert((to.clone(), owned_banner_count), banner_id);
+        <OwnedBannersCount<T>>::insert(&to, new_owned_banner_count);
+        <OwnedBannersIndex<T>>::insert(banner_id, owned_banner_count);
+        <DistinctOwnerCount<T>>::put(distinct_owner_count);
+
+        Self::deposit_event(RawEvent::CreateBanner(to, banner_id));
+
+        Ok(())
+    }
+
+    /// Removes `banner_id` and every index entry that refers to it.
+    fn burn(owner: T::AccountId, banner_id: T::Hash, banner: Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>) -> Result {
+        let owned_banner_count = Self::owned_banner_count(&owner);
+        let new_owned_banner_count = Self::decrement(owned_banner_count, "Burn causes underflow of owner banner balance")?;
+
+        let all_banners_count = Self::all_banners_count();
+        let new_all_banners_count = Self::decrement(all_banners_count, "Burn causes underflow of total supply")?;
+
+        let mut distinct_owner_count = Self::distinct_owner_count();
+        if new_owned_banner_count == 0 {
+            distinct_owner_count = Self::decrement(distinct_owner_count, "Burn causes underflow of banner owners")?;
+        }
+
+        let all_index = <AllBannersIndex<T>>::get(banner_id);
+        if all_index != new_all_banners_count {
+            let last_banner_id = <AllBannersArray<T>>::get(new_all_banners_count);
+            <AllBannersArray<T>>::insert(all_index, last_banner_id);
+            <AllBannersIndex<T>>::insert(last_banner_id, all_index);
+        }
+        <AllBannersArray<T>>::remove(new_all_banners_count);
+        <AllBannersIndex<T>>::remove(banner_id);
+        <AllBannersCount<T>>::put(new_all_banners_count);
+
+        let owned_index = <OwnedBannersIndex<T>>::get(banner_id);
+        if owned_index != new_owned_banner_count {
+            let last_banner_id = <OwnedBannersArray<T>>::get((owner.clone(), new_owned_banner_count));
+            <OwnedBannersArray<T>>::insert((owner.clone(), owned_index), last_banner_id);
+            <OwnedBannersIndex<T>>::insert(last_banner_id, owned_index);
+        }
+        <OwnedBannersArray<T>>::remove((owner.clone(), new_owned_banner_count));
+        <OwnedBannersIndex<T>>::remove(banner_id);
+        <OwnedBannersCount<T>>::insert(&owner, new_owned_banner_count);
+        <DistinctOwnerCount<T>>::put(distinct_owner_count);
+
+        <BannerByName<T>>::remove(Self::name_key(&banner.name));
+        Self::unindex_image_url(&banner.image_url, banner_id);
+        <Banners<T>>::remove(banner_id);
+        <BannerOwner<T>>::remove(banner_id);
+        <Creator<T>>::remove(banner_id);
+        <BannerRoyalty<T>>::remove(banner_id);
+        <OwnershipHistory<T>>::remove(banner_id);
+        <LastSalePrice<T>>::remove(banner_id);
+        <Verified<T>>::remove(banner_id);
+        <AuctionManager<T>>::remove(banner_id);
+        for lang in <LocalizedLanguages<T>>::take(banner_id) {
+            <LocalizedMetadata<T>>::remove((banner_id, lang));
+        }
+        <FixedPrice<T>>::remove(banner_id);
+        <RedeemValue<T>>::remove(banner_id);
+        <BidCount<T>>::remove(banner_id);
+        <Auctioned<T>>::remove(banner_id);
+        <AuctionCount<T>>::remove(banner_id);
+        <LastAuctionEnd<T>>::remove(banner_id);
+        <AuctionResult<T>>::remove(banner_id);
+
+        Self::deposit_event(RawEvent::Burned(owner, banner_id));
+
+        Ok(())
+    }
+
+    /// Bids are accepted strictly before `bid_end_height`; settlement is permitted once the
+    /// current block reaches `bid_end_height + SettlementGracePeriod`.
+    fn auction_phase(bid_end_height: T::BlockNumber) -> AuctionPhase {
+        let now = <system::Module<T>>::block_number();
+        if now < bid_end_height {
+            AuctionPhase::Bidding
+        } else if now < bid_end_height.saturating_add(Self::settlement_grace_period()) {
+            AuctionPhase::AwaitingSettlement
+        } else {
+            AuctionPhase::Settleable
+        }
+    }
+
+    /// Closes an auction now, whether or not it has run its course: the banner goes to the
+    /// highest bidder, or stays with `owner` if nobody outbid the starting price.
+    fn settle(owner: T::AccountId, banner_id: T::Hash, mut banner: Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>) -> rstd::result::Result<AuctionOutcome<T::AccountId, T::Balance>, &'static str> {
+        let final_price = banner.current_price;
+        let final_bidder = banner.current_bidder.clone();
+        let has_real_bid = banner.has_real_bid;
+        // Auctions aborted early end now rather than at their scheduled end.
+        let end_height = banner.bid_end_height.min(<system::Module<T>>::block_number());
+        <LastAuctionEnd<T>>::insert(banner_id, end_height);
+        if has_real_bid {
+            Self::stop_leading(&final_bidder, banner_id);
+        }
+
+        banner.can_bid = false;
+        banner.bid_end_height = Self::blocks(0);
+        banner.current_price = Self::zero_balance();
+        banner.has_real_bid = false;
+        <Banners<T>>::insert(banner_id, banner);
+        let allowed_bidders = <AllowedBidders<T>>::take(banner_id);
+        let bid_limits = <BidLimits<T>>::take(banner_id);
+        let auto_relist = <AutoRelist<T>>::take(banner_id);
+        let listing_deposit = <HeldListingDeposit<T>>::take(banner_id);
+        let mut relisted = false;
+        let runner_up = <PreviousBid<T>>::take(banner_id);
+        let escrow = <BidEscrow<T>>::take(banner_id);
+        <ListingMetadata<T>>::remove(banner_id);
+        <LastBidBlock<T>>::remove_prefix(&banner_id);
+        <ActiveAuctionCount<T>>::mutate(&owner, |n| *n = n.saturating_sub(1));
+
+        let last_index = Self::active_auctions_count().saturating_sub(1);
+        let index = <ActiveAuctionsIndex<T>>::take(banner_id);
+        if index != last_index {
+            let last_banner_id = <ActiveAuctionsArray<T>>::get(last_index);
+            <ActiveAuctionsArray<T>>::insert(index, last_banner_id);
+            <ActiveAuctionsIndex<T>>::insert(last_banner_id, index);
+        }
+        <ActiveAuctionsArray<T>>::remove(last_index);
+        <ActiveAuctionsCount<T>>::put(last_index);
+
+        // Deposits to hand back once every storage write below is done.
+        let mut released = Vec::new();
+        let outcome = if !has_real_bid {
+            // 流拍. A high bidder's deposit is only still held if the auction was pulled.
+            released.extend(escrow);
+            Self::deposit_event(RawEvent::Abort(owner.clone(), banner_id));
+            Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Aborted));
+            T::OnAuctionSettled::on_auction_settled(&banner_id, &owner, None, Self::zero_balance(), AuctionState::Aborted);
+
+            // The seller's auction count was just released, so the relist can't hit their limit.
+            // It also skips the reauction cooldown: the relists were agreed when the auction was
+            // listed, and the cooldown would otherwise cancel every one of them.
+            if let Some((count, decrement)) = auto_relist.filter(|&(count, _)| count > 0) {
+                // A cut larger than the price ends the relisting instead of wrapping around.
+                if let Some(relist_price) = final_price.checked_sub(&decrement) {
+                    if !allowed_bidders.is_empty() {
+                        <AllowedBidders<T>>::insert(banner_id, allowed_bidders);
+                    }
+                    if bid_limits != (None, None) {
+                        <BidLimits<T>>::insert(banner_id, bid_limits);
+                    }
+                    if count > 1 {
+                        <AutoRelist<T>>::insert(banner_id, (count - 1, decrement));
+                    }
+                    Self::start_auction(owner.clone(), banner_id, Self::banner(banner_id), relist_price, Self::blocks(AUCTION_DURATION));
+                    relisted = true;
+                }
+            }
+
+            AuctionOutcome::Aborted
+        } else if let Some(&(_, deposit)) = escrow.as_ref() {
+            // An installment auction: the banner waits for the winner to pay the rest.
+            let runner_up = runner_up.filter(|(bidder, _)| *bidder != final_bidder);
+            Self::await_payment(banner_id, final_bidder.clone(), final_price, deposit, runner_up);
+
+            AuctionOutcome::AwaitingPayment { winner: final_bidder, price: final_price }
+        } else {
+            // 有效成交
+            Self::complete_sale(owner.clone(), final_bidder.clone(), banner_id, final_price, end_height)?;
+
+            AuctionOutcome::Sold { winner: final_bidder, price: final_price }
+        };
+
+        // A relisted auction keeps the seller's deposit until it finally settles.
+        if let Some((depositor, deposit)) = listing_deposit {
+            if relisted {
+                <HeldListingDeposit<T>>::insert(banner_id, (depositor, deposit));
+            } else {
+                released.push((depositor, deposit));
+            }
+        }
+
+        // Funds move last, so a currency hook that calls back in finds the auction settled.
+        for (holder, held) in released.iter() {
+            Self::release_reserved(holder, *held);
+        }
+        for (holder, held) in released {
+            <balances::Module<T> as ReservableCurrency<_>>::unreserve(&holder, held);
+        }
+
+        Ok(outcome)
+    }
+
+    /// Settles `banner_id` once its auction is over and the grace period has passed. Every
+    /// path that closes an auction on schedule goes through here.
+    fn settle_auction(banner_id: T::Hash) -> rstd::result::Result<AuctionOutcome<T::AccountId, T::Balance>, &'static str> {
+        ensure!(<Banners<T>>::exists(banner_id), "This banner does not exist");
+
+        let banner = Self::banner(banner_id);
+        ensure!(banner.can_bid, "This banner is not on auction");
+        ensure!(Self::auction_phase(banner.bid_end_height) == AuctionPhase::Settleable, "This auction can't be settled yet");
+
+        let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+        Self::settle(owner, banner_id, banner)
+    }
+
+    /// Hands a sold banner to `winner`, who may already own it, in which case there is nothing
+    /// to move.
+    fn complete_sale(owner: T::AccountId, winner: T::AccountId, banner_id: T::Hash, price: T::Balance, end_height: T::BlockNumber) -> Result {
+        if winner != owner {
+            Self::transfer_from(owner.clone(), winner.clone(), banner_id)?;
+        }
+        Self::record_sale(banner_id, price);
+        <AuctionResult<T>>::insert(banner_id, (winner.clone(), price, end_height));
+        T::OnAuctionSettled::on_auction_settled(&banner_id, &owner, Some(&winner), price, AuctionState::Ended);
+        Self::deposit_event(RawEvent::Deal(winner, banner_id, price));
+        Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Ended));
+        Ok(())
+    }
+
+    /// The deposit reserved for a bid of `bid_price`, or `None` when auctions are paid as
+    /// bids come in.
+    fn installment_escrow(bid_price: T::Balance) -> Option<T::Balance> {
+        let percent = Self::installment_deposit_percent();
+        if percent == 0 {
+            return None;
+        }
+        Some(Self::percent_of(bid_price, percent, true))
+    }
+
+    /// Moves the high bidder's deposit on `banner_id` to `bidder`. The caller has checked that
+    /// `bidder` can afford `escrow` once any deposit they already hold here is released.
+    fn escrow_bid(bidder: &T::AccountId, banner_id: T::Hash, escrow: T::Balance) -> Result {
+        if let Some((holder, held)) = <BidEscrow<T>>::take(banner_id) {
+            <balances::Module<T> as ReservableCurrency<_>>::unreserve(&holder, held);
+            Self::release_reserved(&holder, held);
+        }
+        <balances::Module<T> as ReservableCurrency<_>>::reserve(bidder, escrow)?;
+        <ReservedByBidder<T>>::mutate(bidder, |reserved| *reserved = reserved.saturating_add(escrow));
+        <BidEscrow<T>>::insert(banner_id, (bidder.clone(), escrow));
+        Ok(())
+    }
+
+    fn await_payment(banner_id: T::Hash, winner: T::AccountId, price: T::Balance, deposit: T::Balance, runner_up: Option<(T::AccountId, T::Balance)>) {
+        let deadline = <system::Module<T>>::block_number().saturating_add(Self::payment_window());
+        <AwaitingPayment<T>>::insert(banner_id, PendingPayment { winner: winner.clone(), price, deposit, deadline, runner_up });
+        Self::deposit_event(RawEvent::PaymentDue(banner_id, winner, price - deposit, deadline));
+    }
+
+    fn ensure_not_awaiting_payment(banner_id: T::Hash) -> Result {
+        ensure!(!<AwaitingPayment<T>>::exists(banner_id), "This banner is awaiting payment");
+        Ok(())
+    }
+
+    /// Reserves `ListingDeposit` from `seller` for the auction about to start on `banner_id`.
+    fn reserve_listing_deposit(seller: &T::AccountId, banner_id: T::Hash) -> Result {
+        let deposit = Self::listing_deposit();
+        if !deposit.is_zero() {
+            <balances::Module<T> as ReservableCurrency<_>>::reserve(seller, deposit)
+                .map_err(|_| "Not enough balance for the listing deposit")?;
+            <ReservedByBidder<T>>::mutate(seller, |reserved| *reserved = reserved.saturating_add(deposit));
+            <HeldListingDeposit<T>>::insert(banner_id, (seller.clone(), deposit));
+        }
+        Ok(())
+    }
+
+    /// An auction that ends the block it starts could be settled before anyone had a chance to
+    /// bid. `auction_banner` always uses `AUCTION_DURATION` and doesn't need this check.
+    fn ensure_min_duration(duration: T::BlockNumber) -> Result {
+        ensure!(!duration.is_zero() && duration >= Self::min_auction_duration(), "duration too short");
+        Ok(())
+    }
+
+    /// Checks that some bid on an auction starting at `starting_price` would fall within
+    /// `min_bid` and `max_bid`.
+    fn ensure_valid_bid_limits(starting_price: T::Balance, min_bid: Option<T::Balance>, max_bid: Option<T::Balance>) -> Result {
+        if let Some(max_bid) = max_bid {
+            ensure!(max_bid > starting_price, "Maximum bid must be above the starting price");
+            ensure!(min_bid.map_or(true, |min_bid| min_bid <= max_bid), "Minimum bid can't exceed the maximum bid");
+        }
+        Ok(())
+    }
+
+    fn ensure_within_max_duration(duration: T::BlockNumber) -> Result {
+        let max_duration = Self::max_auction_duration();
+        ensure!(max_duration.is_zero() || duration <= max_duration, "Auction duration exceeds the maximum");
+        Ok(())
+    }
+
+    /// The banner's owner, provided `who` is the owner or the auction manager they appointed.
+    fn ensure_owner_or_manager(banner_id: T::Hash, who: &T::AccountId) -> rstd::result::Result<T::AccountId, &'static str> {
+        let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+        ensure!(owner == *who || Self::auction_manager(banner_id).as_ref() == Some(who), "You do not own this banner");
+        Ok(owner)
+    }
+
+    fn ensure_not_blocked(who: &T::AccountId) -> Result {
+        ensure!(!Self::is_blocked(who), "account blocked");
+        Ok(())
+    }
+
+    fn ensure_reauction_cooldown(banner_id: T::Hash) -> Result {
+        let cooldown = Self::reauction_cooldown();
+        if let Some(ended) = Self::last_auction_end(banner_id).filter(|_| !cooldown.is_zero()) {
+            let now = <system::Module<T>>::block_number();
+            ensure!(now >= ended.saturating_add(cooldown), "banner in cooldown");
+        }
+        Ok(())
+    }
+
+    fn ensure_below_auction_limit(seller: &T::AccountId) -> Result {
+        let limit = Self::max_active_auctions_per_account();
+        ensure!(limit == 0 || Self::active_auction_count(seller) < limit, "Too many active auctions for this account");
+        Ok(())
+    }
+
+    /// Opens an auction for `banner_id` with `seller` as the initial bidder, ending `duration`
+    /// blocks from now, or at the last representable block if that is sooner. Callers must have
+    /// checked ownership and that it isn't already on auction.
+    fn start_auction(seller: T::AccountId, banner_id: T::Hash, mut banner: Banner<T::Hash, T::Balance, T::AccountId, T::BlockNumber>, starting_price: T::Balance, duration: T::BlockNumber) {
+        banner.current_price = starting_price;
+        banner.can_bid = true;
+        banner.current_bidder = seller.clone();
+        banner.bid_end_height = <system::Module<T>>::block_number().saturating_add(duration);
+        banner.has_real_bid = false;
+
+        <ListingMetadata<T>>::insert(banner_id, BannerMetadata {
+            name: banner.name.clone(),
+            image_url: banner.image_url.clone(),
+            desc: banner.desc.clone(),
+        });
+        <Banners<T>>::insert(banner_id, banner);
+        <BidCount<T>>::remove(banner_id);
+        <Auctioned<T>>::insert(banner_id, true);
+        <AuctionCount<T>>::mutate(banner_id, |n| *n = n.saturating_add(1));
+        <AuctionResult<T>>::remove(banner_id);
+        <ActiveAuctionCount<T>>::mutate(&seller, |n| *n = n.saturating_add(1));
+
+        let index = Self::active_auctions_count();
+        <ActiveAuctionsArray<T>>::insert(index, banner_id);
+        <ActiveAuctionsIndex<T>>::insert(banner_id, index);
+        <ActiveAuctionsCount<T>>::put(index.saturating_add(1));
+
+        Self::deposit_event(RawEvent::StartAuction(seller, banner_id, starting_price));
+        Self::deposit_event(RawEvent::AuctionStateChanged(banner_id, AuctionState::Started));
+    }
+
+    /// Moves `banner_id` from `from` to `to`.
+    ///
+    /// Ownership is tracked independently of balances: if `to` is later reaped for falling below
+    /// the existential deposit, `BannerOwner` still records it as the owner. Chains that want to
+    /// avoid auction winners being reaped by their own bid should enable `RequireLiveBidders`.
+    ///
+    /// Every ownership change except `migrate_account` goes through here, so this is also where
+    /// banners on a live auction are protected from being moved. Settlement closes the auction
+    /// before calling it.
+    fn transfer_from(from: T::AccountId, to: T::AccountId, banner_id: T::Hash) -> Result {
+        let owner = Self::owner_of(banner_id).ok_or("No owner for this banner")?;
+
+        ensure!(owner == from, "'from' account does not own this banner");
+        ensure!(from != to, "'from' and 'to' are the same account");
+        ensure!(!Self::banner(banner_id).can_bid, "cannot transfer during auction");
+        ensure!(!Self::is_retired(banner_id), "This banner has been retired");
+        Self::ensure_not_awaiting_payment(banner_id)?;
+
+        Self::reassign(from, to, banner_id)
+    }
+
+    /// Updates every ownership index to move `banner_id` from `from` to `to`. Callers must have
+    /// checked that `from` owns it.
+    fn reassign(from: T::AccountId, to: T::AccountId, banner_id: T::Hash) -> Result {
+        let owned_banner_count_from = Self::owned_banner_count(&from);
+        let owned_banner_count_to = Self::owned_banner_count(&to);
+
+        let new_owned_banner_count_to = Self::increment(owned_banner_count_to, "Transfer causes overflow of 'to' banner balance")?;
+        let new_owned_banner_count_from = Self::decrement(owned_banner_count_from, "Transfer causes underflow of 'from' banner balance")?;
+
+        let mut distinct_owner_count = Self::distinct_owner_count();
+        if owned_banner_count_to == 0 {
+            distinct_owner_count = Self::increment(distinct_owner_count, "Transfer causes overflow of banner owners")?;
+        }
+        if new_owned_banner_count_from == 0 {
+            distinct_owner_count = Self::decrement(distinct_owner_count, "Transfer causes underflow of banner owners")?;
+        }
+
+        let banner_index = <OwnedBannersIndex<T>>::get(banner_id);
+        if banner_index != new_owned_banner_count_from {
+            let last_banner_id = <OwnedBannersArray<T>>::get((from.clone(), new_owned_banner_count_from));
+            <OwnedBannersArray<T>>::insert((from.clone(), banner_index), last_banner_id);
+            <OwnedBannersIndex<T>>::insert(last_banner_id, banner_index);
+        }
+
+        <BannerOwner<T>>::insert(&banner_id, &to);
+        <OwnedBannersIndex<T>>::insert(banner_id, owned_banner_count_to);
+
+        <OwnedBannersArray<T>>::remove((from.clone(), new_owned_banner_count_from));
+        <OwnedBannersArray<T>>::insert((to.clone(), owned_banner_count_to), banner_id);
+
+        <OwnedBannersCount<T>>::insert(&from, new_owned_banner_count_from);
+        <OwnedBannersCount<T>>::insert(&to, new_owned_banner_count_to);
+        <DistinctOwnerCount<T>>::put(distinct_owner_count);
+
+        // A listing is the previous owner's offer to sell; it doesn't carry over.
+        <FixedPrice<T>>::remove(banner_id);
+        <AuctionManager<T>>::remove(banner_id);
+        Self::record_owner(banner_id, to.clone());
+
+        Self::deposit_event(RawEvent::Transferred(from, to, banner_id));
+
+        Ok(())
+    }
+
+    /// Appends `owner` to the banner's history, dropping the oldest entry once it's full.
+    fn record_owner(banner_id: T::Hash, owner: T::AccountId) {
+        let now = <system::Module<T>>::block_number();
+        <OwnershipHistory<T>>::mutate(banner_id, |history| {
+            if history.len() >= MAX_HISTORY {
+                history.remove(0);
+            }
+            // Can't fail: there is always room after dropping the oldest entry.
+            let _ = Self::bounded_push(history, (owner, now), MAX_HISTORY, "Ownership history is full");
+        });
+    }
+
+    /// Appends `value` to a `Vec` bound for storage, failing with `err` rather than growing it
+    /// past `max`. Every append to `Vec`-valued storage goes through here.
+    fn bounded_push<V>(items: &mut Vec<V>, value: V, max: usize, err: &'static str) -> Result {
+        ensure!(items.len() < max, err);
+        items.push(value);
+        Ok(())
+    }
+
+    /// Checks that the enumeration indexes agree with each other and with `BannerOwner`,
+    /// returning the first inconsistency found. Walks every banner, so only for tests and
+    /// offline checks.
+    #[cfg(any(test, feature = "try-runtime"))]
+    pub fn do_try_state() -> Result {
+        let all_banners_count = Self::all_banners_count();
+        ensure!(!<AllBannersArray<T>>::exists(all_banners_count), "AllBannersArray has entries past AllBannersCount");
+
+        let mut owners: Vec<T::AccountId> = Vec::new();
+        for index in 0..all_banners_count {
+            ensure!(<AllBannersArray<T>>::exists(index), "AllBannersArray is missing an entry below AllBannersCount");
+            let banner_id = Self::banner_by_index(index);
+            ensure!(<Banners<T>>::exists(banner_id), "AllBannersArray lists a banner that doesn't exist");
+            ensure!(<AllBannersIndex<T>>::get(banner_id) == index, "AllBannersIndex doesn't point back to AllBannersArray");
+
+            let owner = Self::owner_of(banner_id).ok_or("Banner has no owner")?;
+            let owned_index = <OwnedBannersIndex<T>>::get(banner_id);
+            ensure!(owned_index < Self::owned_banner_count(&owner), "OwnedBannersIndex is past the owner's OwnedBannersCount");
+            ensure!(Self::banner_of_owner_by_index((owner.clone(), owned_index)) == banner_id, "OwnedBannersIndex doesn't point back to OwnedBannersArray");
+
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+
+        // Every banner sits in its owner's list at a distinct slot, so the lists have no room
+        // left for strays once their lengths add up to the total.
+        let owned_total = owners.iter().fold(0u64, |total, owner| total.saturating_add(Self::owned_banner_count(owner)));
+        ensure!(owned_total == all_banners_count, "OwnedBannersCount doesn't add up to AllBannersCount");
+        ensure!(owners.len() as u64 == Self::distinct_owner_count(), "DistinctOwnerCount doesn't match the number of owners");
+
+        Ok(())
+    }
+}
+
+impl<T: Trait> Nft<T::AccountId> for Module<T> {
+    type Id = T::Hash;
+
+    fn exists(id: &T::Hash) -> bool {
+        <Banners<T>>::exists(id)
+    }
+
+    fn owner(id: &T::Hash) -> Option<T::AccountId> {
+        Self::owner_of(id)
+    }
+
+    fn transfer(from: &T::AccountId, to: &T::AccountId, id: &T::Hash) -> Result {
+        Self::ensure_not_blocked(from)?;
+        Self::ensure_not_blocked(to)?;
+        Self::transfer_from(from.clone(), to.clone(), *id)
+    }
+}
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use runtime_io::with_externalities;
+    use primitives::{H256, Blake2Hasher};
+    use support::{impl_outer_origin, impl_outer_event, assert_ok, assert_noop};
+    use std::cell::RefCell;
+    use runtime_primitives::{
+        BuildStorage,
+        traits::{BlakeTwo256, IdentityLookup, OnFinalize},
+        testing::{Digest, DigestItem, Header}
+    };
+
+    impl_outer_origin! {
+        pub enum Origin for Test {}
+    }
+
+    mod banners {
+        pub use super::super::Event;
+    }
+
+    impl_outer_event! {
+        pub enum TestEvent for Test {
+            balances<T>, banners<T>,
+        }
+    }
+
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct Test;
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type Digest = Digest;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = TestEvent;
+        type Log = DigestItem;
+    }
+    impl balances::Trait for Test {
+        type Balance = u64;
+        type OnFreeBalanceZero = ();
+        type OnNewAccount = ReentrancyProbe;
+        type Event = TestEvent;
+        type TransactionPayment = ();
+        type TransferPayment = ();
+        type DustRemoval = ();
+    }
+
+    thread_local! {
+        static PROBED_BANNER: RefCell<Option<H256>> = RefCell::new(None);
+        static PROBES: RefCell<Vec<(Option<u64>, bool, Result)>> = RefCell::new(Vec::new());
+    }
+
+    /// A currency hook that runs in the middle of a payment that creates an account. Once armed
+    /// with a banner, it records the banner's owner and whether it is awaiting payment, and
+    /// tries to pay for it again as account 3.
+    pub struct ReentrancyProbe;
+    impl system::OnNewAccount<u64> for ReentrancyProbe {
+        fn on_new_account(_who: &u64) {
+            if let Some(banner_id) = PROBED_BANNER.with(|probed| probed.borrow_mut().take()) {
+                let probe = (
+                    Banners::owner_of(banner_id),
+                    Banners::awaiting_payment(banner_id).is_some(),
+                    Banners::finalize_payment(Origin::signed(3), banner_id),
+                );
+                PROBES.with(|probes| probes.borrow_mut().push(probe));
+            }
+        }
+    }
+
+    pub struct MockOracle;
+    impl PriceOracle<H256, u64> for MockOracle {
+        fn suggested_reserve(banner_id: &H256) -> Option<u64> {
+            if <Banners<Test>>::exists(banner_id) {
+                Some(100)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Only account 2 holds an attestation.
+    pub struct MockAttestation;
+    impl AttestationCheck<u64> for MockAttestation {
+        fn is_attested(who: &u64) -> bool {
+            *who == 2
+        }
+    }
+
+    thread_local! {
+        static SETTLED: RefCell<Vec<(H256, u64, Option<u64>, u64, AuctionState)>> = RefCell::new(Vec::new());
+    }
+
+    /// Records every settlement so tests can inspect what the hook was given.
+    pub struct MockSettlementHook;
+    impl OnAuctionSettled<H256, u64, u64> for MockSettlementHook {
+        fn on_auction_settled(banner_id: &H256, seller: &u64, buyer: Option<&u64>, price: u64, outcome: AuctionState) {
+            SETTLED.with(|settled| settled.borrow_mut().push((*banner_id, *seller, buyer.cloned(), price, outcome)));
+        }
+    }
+
+    fn settled_auctions() -> Vec<(H256, u64, Option<u64>, u64, AuctionState)> {
+        SETTLED.with(|settled| settled.borrow().clone())
+    }
+
+    impl Trait for Test {
+        type Event = TestEvent;
+        type PriceOracle = MockOracle;
+        type AttestationCheck = MockAttestation;
+        type OnAuctionSettled = MockSettlementHook;
+    }
+    type System = system::Module<Test>;
+    type Balances = balances::Module<Test>;
+    type Banners = Module<Test>;
+
+    const REWARD_POOL: u64 = 4;
+    const FEE_ACCOUNT: u64 = 5;
+    const BURN_ADDRESS: u64 = 6;
+
+    struct ExtBuilder {
+        existential_deposit: u64,
+        require_live_bidders: bool,
+        bid_deposit: u64,
+        listing_deposit: u64,
+        installment_deposit_percent: u32,
+        payment_window: u64,
+        high_value_threshold: u64,
+        settlement_grace_period: u64,
+        max_active_auctions_per_account: u32,
+        max_offers_per_banner: u32,
+        anti_snipe_window: u64,
+        commission_percent: u32,
+        force_cancel_penalty_percent: u32,
+        decline_penalty_percent: u32,
+        royalty_percent: u32,
+        max_royalty_percent: u32,
+        rounding_policy: RoundingPolicy,
+        fee_payer: FeePayer,
+        max_auction_duration: u64,
+        min_auction_duration: u64,
+        bid_cooldown: u64,
+        reauction_cooldown: u64,
+        bid_increment_tiers: Vec<(u64, u64)>,
+        case_insensitive_names: bool,
+        supply_sample_interval: u64,
+        supply_retention: u32,
+    }
+
+    impl Default for ExtBuilder {
+        fn default() -> Self {
+            ExtBuilder {
+                existential_deposit: 0,
+                require_live_bidders: false,
+                bid_deposit: 0,
+                listing_deposit: 0,
+                installment_deposit_percent: 0,
+                payment_window: 0,
+                high_value_threshold: 0,
+                settlement_grace_period: 0,
+                max_active_auctions_per_account: 0,
+                max_offers_per_banner: 0,
+                anti_snipe_window: 0,
+                commission_percent: 0,
+                force_cancel_penalty_percent: 0,
+                decline_penalty_percent: 0,
+                royalty_percent: 0,
+                max_royalty_percent: 100,
+                rounding_policy: RoundingPolicy::FavorSeller,
+                fee_payer: FeePayer::Seller,
+                max_auction_duration: 0,
+                min_auction_duration: 0,
+                bid_cooldown: 0,
+                reauction_cooldown: 0,
+                bid_increment_tiers: vec![],
+                case_insensitive_names: false,
+                supply_sample_interval: 0,
+                supply_retention: 0,
+            }
+        }
+    }
+
+    impl ExtBuilder {
+        fn existential_deposit(mut self, existential_deposit: u64) -> Self {
+            self.existential_deposit = existential_deposit;
+            self
+        }
+
+        fn require_live_bidders(mut self, require_live_bidders: bool) -> Self {
+            self.require_live_bidders = require_live_bidders;
+            self
+        }
+
+        fn bid_deposit(mut self, bid_deposit: u64) -> Self {
+            self.bid_deposit = bid_deposit;
+            self
+        }
+
+        fn listing_deposit(mut self, listing_deposit: u64) -> Self {
+            self.listing_deposit = listing_deposit;
+            self
+        }
+
+        fn installments(mut self, deposit_percent: u32, payment_window: u64) -> Self {
+            self.installment_deposit_percent = deposit_percent;
+            self.payment_window = payment_window;
+            self
+        }
+
+        fn high_value_threshold(mut self, high_value_threshold: u64) -> Self {
+            self.high_value_threshold = high_value_threshold;
+            self
+        }
+
+        fn settlement_grace_period(mut self, settlement_grace_period: u64) -> Self {
+            self.settlement_grace_period = settlement_grace_period;
+            self
+        }
+
+        fn max_active_auctions_per_account(mut self, max_active_auctions_per_account: u32) -> Self {
+            self.max_active_auctions_per_account = max_active_auctions_per_account;
+            self
+        }
+
+        fn max_offers_per_banner(mut self, max_offers_per_banner: u32) -> Self {
+            self.max_offers_per_banner = max_offers_per_banner;
+            self
+        }
+
+        fn anti_snipe_window(mut self, anti_snipe_window: u64) -> Self {
+            self.anti_snipe_window = anti_snipe_window;
+            self
+        }
+
+        fn force_cancel_penalty_percent(mut self, force_cancel_penalty_percent: u32) -> Self {
+            self.force_cancel_penalty_percent = force_cancel_penalty_percent;
+            self
+        }
+
+        fn decline_penalty_percent(mut self, decline_penalty_percent: u32) -> Self {
+            self.decline_penalty_percent = decline_penalty_percent;
+            self
+        }
+
+        fn commission_percent(mut self, commission_percent: u32) -> Self {
+            self.commission_percent = commission_percent;
+            self
+        }
+
+        fn royalty_percent(mut self, royalty_percent: u32) -> Self {
+            self.royalty_percent = royalty_percent;
+            self
+        }
+
+        fn max_royalty_percent(mut self, max_royalty_percent: u32) -> Self {
+            self.max_royalty_percent = max_royalty_percent;
+            self
+        }
+
+        fn rounding_policy(mut self, rounding_policy: RoundingPolicy) -> Self {
+            self.rounding_policy = rounding_policy;
+            self
+        }
+
+        fn fee_payer(mut self, fee_payer: FeePayer) -> Self {
+            self.fee_payer = fee_payer;
+            self
+        }
+
+        fn max_auction_duration(mut self, max_auction_duration: u64) -> Self {
+            self.max_auction_duration = max_auction_duration;
+            self
+        }
+
+        fn min_auction_duration(mut self, min_auction_duration: u64) -> Self {
+            self.min_auction_duration = min_auction_duration;
+            self
+        }
+
+        fn bid_cooldown(mut self, bid_cooldown: u64) -> Self {
+            self.bid_cooldown = bid_cooldown;
+            self
+        }
+
+        fn reauction_cooldown(mut self, reauction_cooldown: u64) -> Self {
+            self.reauction_cooldown = reauction_cooldown;
+            self
+        }
+
+        fn bid_increment_tiers(mut self, bid_increment_tiers: Vec<(u64, u64)>) -> Self {
+            self.bid_increment_tiers = bid_increment_tiers;
+            self
+        }
+
+        fn case_insensitive_names(mut self, case_insensitive_names: bool) -> Self {
+            self.case_insensitive_names = case_insensitive_names;
+            self
+        }
+
+        fn supply_sampling(mut self, interval: u64, retention: u32) -> Self {
+            self.supply_sample_interval = interval;
+            self.supply_retention = retention;
+            self
+        }
+
+        fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
+            SETTLED.with(|settled| settled.borrow_mut().clear());
+            let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+            t.extend(balances::GenesisConfig::<Test> {
+                balances: vec![(1, 1000), (2, 1000), (3, 1000), (4, 1000)],
+                transaction_base_fee: 0,
+                transaction_byte_fee: 0,
+                existential_deposit: self.existential_deposit,
+                transfer_fee: 0,
+                creation_fee: 0,
+                vesting: vec![],
+            }.build_storage().unwrap().0);
+            t.extend(GenesisConfig::<Test> {
+                require_live_bidders: self.require_live_bidders,
+                reward_pool: REWARD_POOL,
+                bid_deposit: self.bid_deposit,
+                listing_deposit: self.listing_deposit,
+                installment_deposit_percent: self.installment_deposit_percent,
+                payment_window: self.payment_window,
+                fee_account: FEE_ACCOUNT,
+                burn_address: BURN_ADDRESS,
+                high_value_threshold: self.high_value_threshold,
+                settlement_grace_period: self.settlement_grace_period,
+                max_active_auctions_per_account: self.max_active_auctions_per_account,
+                max_offers_per_banner: self.max_offers_per_banner,
+                anti_snipe_window: self.anti_snipe_window,
+                commission_percent: self.commission_percent,
+                force_cancel_penalty_percent: self.force_cancel_penalty_percent,
+                decline_penalty_percent: self.decline_penalty_percent,
+                royalty_percent: self.royalty_percent,
+                max_royalty_percent: self.max_royalty_percent,
+                rounding_policy: self.rounding_policy,
+                fee_payer: self.fee_payer,
+                max_auction_duration: self.max_auction_duration,
+                min_auction_duration: self.min_auction_duration,
+                bid_cooldown: self.bid_cooldown,
+                reauction_cooldown: self.reauction_cooldown,
+                bid_increment_tiers: self.bid_increment_tiers,
+                case_insensitive_names: self.case_insensitive_names,
+                supply_sample_interval: self.supply_sample_interval,
+                supply_retention: self.supply_retention,
+                allowed_url_schemes: vec![b"ipfs://".to_vec(), b"https://".to_vec()],
+                seconds_per_block: 6,
+            }.build_storage().unwrap().0);
+            t.into()
+        }
+    }
+
+    fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+        ExtBuilder::default().build()
+    }
+
+    /// Creates a banner owned by `who` and returns its id.
+    fn create(who: u64) -> H256 {
+        let name = format!("banner-{}", <Nonce<Test>>::get()).into_bytes();
+        assert_ok!(Banners::create_banner(Origin::signed(who), name, b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+        assert_ok!(Banners::do_try_state());
+        Banners::banner_of_owner_by_index((who, Banners::owned_banner_count(who) - 1))
+    }
+
+    /// Events deposited by this module so far, in order.
+    fn banner_events() -> Vec<Event<Test>> {
+        System::events().into_iter().filter_map(|record| match record.event {
+            TestEvent::banners(event) => Some(event),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn auctions_ending_for_includes_seller_and_high_bidder() {
+        with_externalities(&mut new_test_ext(), || {
+            let selling = create(1);
+            let idle = create(1);
+            let leading = create(2);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), selling, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), leading, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(1), leading, 20));
+
+            let ending = Banners::auctions_ending_for(1);
+            assert_eq!(ending, vec![(selling, AUCTION_DURATION), (leading, AUCTION_DURATION)]);
+            assert!(!ending.iter().any(|(id, _)| *id == idle));
+
+            assert_eq!(Banners::auctions_ending_for(2), vec![(leading, AUCTION_DURATION)]);
+            assert!(Banners::auctions_ending_for(3).is_empty());
+        });
+    }
+
+    #[test]
+    fn live_bidders_are_kept_at_existential_deposit() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).require_live_bidders(true).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 950), "bid would leave bidder below existential deposit");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 900));
+            assert_eq!(Balances::free_balance(&2), 100);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(1), banner_id, 0));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Balances::free_balance(&2), 100);
+        });
+    }
+
+    #[test]
+    fn reaped_winner_still_owns_banner() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 950));
+            assert_eq!(Balances::total_balance(&2), 0);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(1), banner_id, 0));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Banners::owned_banner_count(2), 1);
+        });
+    }
+
+    #[test]
+    fn transfer_and_auction_hands_over_a_live_auction() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 3, banner_id, 10, 5));
+
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::owned_banner_count(3), 1);
+
+            let banner = Banners::banner(banner_id);
+            assert!(banner.can_bid);
+            assert_eq!(banner.current_bidder, 3);
+            assert_eq!(banner.current_price, 10);
+            assert_eq!(banner.bid_end_height, 5);
+
+            let events = banner_events();
+            assert_eq!(events[events.len() - 3..], [
+                RawEvent::Transferred(1, 3, banner_id),
+                RawEvent::StartAuction(3, banner_id, 10),
+                RawEvent::AuctionStateChanged(banner_id, AuctionState::Started),
+            ]);
+        });
+    }
+
+    #[test]
+    fn transfer_and_auction_requires_ownership() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(2), 3, banner_id, 10, 5), "You do not own this banner");
+        });
+    }
+
+    #[test]
+    fn auctions_shorter_than_the_minimum_are_rejected() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 3, banner_id, 10, 0), "duration too short");
+        });
+
+        with_externalities(&mut ExtBuilder::default().min_auction_duration(10).build(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 3, banner_id, 10, 0), "duration too short");
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 3, banner_id, 10, 9), "duration too short");
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 3, banner_id, 10, 10));
+
+            // The fixed-length listing path is unaffected.
+            let listed = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), listed, 10, vec![], None, None, None));
+        });
+    }
+
+    #[test]
+    fn end_heights_saturate_near_the_block_number_limit() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(10).bid_cooldown(10).build(), || {
+            let listed = create(1);
+            let handed_over = create(1);
+
+            System::set_block_number(u64::max_value() - 5);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), listed, 10, vec![], None, None, None));
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, handed_over, 10, 10));
+            assert_eq!(Banners::banner(listed).bid_end_height, u64::max_value());
+            assert_eq!(Banners::banner(handed_over).bid_end_height, u64::max_value());
+
+            assert_noop!(Banners::extend_auction(Origin::signed(1), listed, 1), "Auction end height overflows");
+
+            assert_ok!(Banners::bid(Origin::signed(3), listed, 20));
+            assert_noop!(Banners::bid(Origin::signed(3), listed, 30), "You are bidding too soon after your last bid");
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![listed]));
+            assert!(Banners::banner(listed).can_bid);
+        });
+    }
+
+    #[test]
+    fn name_availability_tracks_taken_and_invalid_names() {
+        with_externalities(&mut new_test_ext(), || {
+            assert!(Banners::is_name_available(b"sunrise".to_vec()));
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"sunrise".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+
+            assert!(!Banners::is_name_available(b"sunrise".to_vec()));
+            assert!(Banners::is_name_available(b"sunset".to_vec()));
+            assert!(!Banners::is_name_available(vec![]));
+            assert!(!Banners::is_name_available(vec![b'a'; MAX_NAME_LENGTH + 1]));
+            assert!(Banners::is_name_available(vec![b'a'; MAX_NAME_LENGTH]));
+
+            assert_noop!(
+                Banners::create_banner(Origin::signed(2), b"sunrise".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()),
+                "This banner name is already taken"
+            );
+        });
+    }
+
+    #[test]
+    fn case_variants_collide_when_names_are_case_insensitive() {
+        with_externalities(&mut ExtBuilder::default().case_insensitive_names(true).build(), || {
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"MyBanner".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_eq!(Banners::banner(banner_id).name, b"MyBanner".to_vec());
+
+            assert!(!Banners::is_name_available(b"mybanner".to_vec()));
+            assert_noop!(
+                Banners::create_banner(Origin::signed(2), b"MYBANNER".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()),
+                "This banner name is already taken"
+            );
+
+            // Recasing your own banner's name is allowed and frees nothing up.
+            assert_ok!(Banners::update_metadata(Origin::signed(1), banner_id, Some(b"mybanner".to_vec()), None, None));
+            assert_eq!(Banners::banner_by_name(b"mybanner".to_vec()), Some(banner_id));
+            assert!(!Banners::is_name_available(b"MyBanner".to_vec()));
+
+            assert_ok!(Banners::burn_banner(Origin::signed(1), banner_id));
+            assert!(Banners::is_name_available(b"MYBANNER".to_vec()));
+        });
+    }
+
+    #[test]
+    fn case_variants_are_distinct_by_default() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"MyBanner".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+            assert_ok!(Banners::create_banner(Origin::signed(2), b"mybanner".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+        });
+    }
+
+    #[test]
+    fn validate_metadata_matches_create_banner() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"taken".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+
+            let cases = vec![
+                (vec![], b"ipfs://url".to_vec(), b"desc".to_vec(), "Banner name must not be empty"),
+                (vec![b'a'; MAX_NAME_LENGTH + 1], b"ipfs://url".to_vec(), b"desc".to_vec(), "Banner name is too long"),
+                (b"taken".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), "This banner name is already taken"),
+                (b"fresh".to_vec(), vec![b'a'; MAX_URL_LENGTH + 1], b"desc".to_vec(), "Image url is too long"),
+                (b"fresh".to_vec(), b"javascript:alert(1)".to_vec(), b"desc".to_vec(), "unsupported url scheme"),
+                (b"fresh".to_vec(), b"ipfs://url".to_vec(), vec![b'a'; MAX_DESC_LENGTH + 1], "Banner description is too long"),
+            ];
+            for (name, url, desc, reason) in cases {
+                assert_eq!(Banners::validate_metadata(&name, &url, &desc), Err(reason));
+                assert_noop!(Banners::create_banner(Origin::signed(1), name, url, desc, H256::zero()), reason);
+            }
+
+            assert_ok!(Banners::validate_metadata(b"fresh", b"ipfs://url", b"desc"));
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"fresh".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+        });
+    }
+
+    #[test]
+    fn burning_without_redeem_value_only_removes_the_banner() {
+        with_externalities(&mut new_test_ext(), || {
+            let kept = create(1);
+            let burned = create(1);
+            assert_ok!(Banners::burn_banner(Origin::signed(1), burned));
+
+            assert!(!<Banners<Test>>::exists(burned));
+            assert_eq!(Banners::owner_of(burned), None);
+            assert_eq!(Banners::all_banners_count(), 1);
+            assert_eq!(Banners::owned_banner_count(1), 1);
+            assert_eq!(Banners::banner_of_owner_by_index((1, 0)), kept);
+            assert_eq!(Balances::free_balance(&1), 1000);
+            assert_eq!(Balances::free_balance(&REWARD_POOL), 1000);
+            assert_eq!(banner_events().last(), Some(&RawEvent::Burned(1, burned)));
+        });
+    }
+
+    #[test]
+    fn burning_redeemable_banner_pays_from_reward_pool() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Banners::create_redeemable_banner(system::RawOrigin::Root.into(), 1,
+                b"loyalty".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), 100, H256::zero()));
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_eq!(Banners::redeem_value(banner_id), 100);
+
+            assert_ok!(Banners::burn_banner(Origin::signed(1), banner_id));
+
+            assert_eq!(Balances::free_balance(&1), 1100);
+            assert_eq!(Balances::free_balance(&REWARD_POOL), 900);
+            assert_eq!(Banners::redeem_value(banner_id), 0);
+            assert_eq!(banner_events().last(), Some(&RawEvent::Redeemed(1, banner_id, 100)));
+        });
+    }
+
+    #[test]
+    fn burning_redeemable_banner_needs_a_funded_pool() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Banners::create_redeemable_banner(system::RawOrigin::Root.into(), 1,
+                b"loyalty".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), 2000, H256::zero()));
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+
+            assert_noop!(Banners::burn_banner(Origin::signed(1), banner_id), "Reward pool cannot cover the redeem value");
+        });
+    }
+
+    #[test]
+    fn burning_keeps_the_banner_if_the_redeem_value_cannot_be_paid() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).build(), || {
+            assert_ok!(Banners::create_redeemable_banner(system::RawOrigin::Root.into(), 1,
+                b"loyalty".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), 50, H256::zero()));
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_ok!(Balances::transfer(Origin::signed(1), 4, 1000));
+
+            assert_noop!(Banners::burn_banner(Origin::signed(1), banner_id),
+                "payment is below the existential deposit needed to recreate the recipient's account");
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+        });
+    }
+
+    #[test]
+    fn collection_stats_track_distinct_owners() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_eq!(Banners::collection_stats(), (0, 0));
+
+            let first = create(1);
+            let second = create(1);
+            let other = create(2);
+            assert_eq!(Banners::collection_stats(), (3, 2));
+
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 3, first, 10, 5));
+            assert_eq!(Banners::collection_stats(), (3, 3));
+
+            assert_ok!(Banners::burn_banner(Origin::signed(2), other));
+            assert_eq!(Banners::collection_stats(), (2, 2));
+
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 3, second, 10, 5));
+            assert_eq!(Banners::collection_stats(), (2, 1));
+        });
+    }
+
+    #[test]
+    fn bid_deposit_is_charged_per_bid() {
+        with_externalities(&mut ExtBuilder::default().bid_deposit(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 10);
+            assert_eq!(Balances::free_balance(&1), 1030);
+            assert_eq!(Balances::free_balance(&2), 995);
+            assert_eq!(Balances::free_balance(&3), 965);
+        });
+    }
+
+    #[test]
+    fn bid_deposit_must_be_affordable() {
+        with_externalities(&mut ExtBuilder::default().bid_deposit(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 998), "Not enough balance to cover the bid");
+        });
+    }
+
+    #[test]
+    fn bid_deposit_is_checked_before_the_bid_is_paid() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).bid_deposit(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Balances::total_balance(&FEE_ACCOUNT), 0);
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 150),
+                "payment is below the existential deposit needed to recreate the recipient's account");
+            assert_eq!(Balances::free_balance(&1), 1000);
+            assert_eq!(Balances::free_balance(&2), 1000);
+        });
+    }
+
+    #[test]
+    fn high_value_bids_need_attestation() {
+        with_externalities(&mut ExtBuilder::default().high_value_threshold(100).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 101), "High-value bids require an attestation");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 101));
+        });
+    }
+
+    #[test]
+    fn zero_high_value_threshold_skips_attestation() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 500));
+            assert!(<() as AttestationCheck<u64>>::is_attested(&3));
+        });
+    }
+
+    #[test]
+    fn zero_bid_deposit_charges_nothing() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 0);
+            assert_eq!(Balances::free_balance(&2), 980);
+        });
+    }
+
+    #[test]
+    fn listing_deposit_is_held_until_settlement() {
+        with_externalities(&mut ExtBuilder::default().listing_deposit(50).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Balances::reserved_balance(&1), 50);
+            assert_eq!(Balances::free_balance(&1), 950);
+            assert_eq!(Banners::reserved_of(1), 50);
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            System::set_block_number(AUCTION_DURATION + 2);
+            assert_ok!(Banners::claim(Origin::signed(2), banner_id));
+
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Balances::reserved_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&1), 1020);
+            assert_eq!(Banners::reserved_of(1), 0);
+            assert_eq!(Banners::held_listing_deposit(banner_id), None);
+        });
+    }
+
+    #[test]
+    fn listing_deposit_must_be_affordable() {
+        with_externalities(&mut ExtBuilder::default().listing_deposit(5000).build(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None),
+                "Not enough balance for the listing deposit");
+            assert!(!Banners::banner(banner_id).can_bid);
+        });
+    }
+
+    #[test]
+    fn force_cancel_hands_the_listing_deposit_to_the_bidder() {
+        with_externalities(&mut ExtBuilder::default().listing_deposit(50).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+
+            assert_ok!(Banners::force_cancel_auction(Origin::signed(1), banner_id));
+            assert_eq!(Balances::free_balance(&2), 1050);
+            assert_eq!(Balances::reserved_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&1), 950);
+            assert_eq!(Banners::reserved_of(1), 0);
+        });
+    }
+
+    #[test]
+    fn update_metadata_applies_only_provided_fields() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let old_name = Banners::banner(banner_id).name;
+
+            assert_ok!(Banners::update_metadata(Origin::signed(1), banner_id,
+                Some(b"renamed".to_vec()), Some(b"ipfs://new".to_vec()), None));
+
+            let banner = Banners::banner(banner_id);
+            assert_eq!(banner.name, b"renamed".to_vec());
+            assert_eq!(banner.image_url, b"ipfs://new".to_vec());
+            assert_eq!(banner.desc, b"desc".to_vec());
+            assert_eq!(Banners::banner_by_name(b"renamed".to_vec()), Some(banner_id));
+            assert_eq!(Banners::banner_by_name(old_name), None);
+            assert_eq!(banner_events().last(), Some(&RawEvent::MetadataUpdated(1, banner_id)));
+        });
+    }
+
+    #[test]
+    fn update_metadata_checks_ownership_and_name() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let taken = Banners::banner(create(2)).name;
+
+            assert_noop!(Banners::update_metadata(Origin::signed(2), banner_id, None, Some(b"ipfs://url".to_vec()), None),
+                "You do not own this banner");
+            assert_noop!(Banners::update_metadata(Origin::signed(1), banner_id, Some(taken), None, None),
+                "This banner name is already taken");
+            assert_noop!(Banners::update_metadata(Origin::signed(1), banner_id, Some(vec![]), None, None),
+                "Banner name must not be empty");
+        });
+    }
+
+    #[test]
+    fn bids_close_at_end_height_and_settle_without_grace() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION - 1);
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert!(banner_events().contains(&RawEvent::Deal(2, banner_id, 20)));
+        });
+    }
+
+    #[test]
+    fn settlement_waits_for_grace_period() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(2).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 30), "This auction has ended and is awaiting settlement");
+            System::set_block_number(AUCTION_DURATION + 1);
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 30), "This auction has ended and is awaiting settlement");
+
+            System::set_block_number(AUCTION_DURATION + 2);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+        });
+    }
+
+    #[test]
+    fn owned_banner_at_matches_two_step_read() {
+        with_externalities(&mut new_test_ext(), || {
+            create(1);
+            create(1);
+
+            for index in 0..2 {
+                let banner_id = Banners::banner_of_owner_by_index((1, index));
+                assert_eq!(Banners::owned_banner_at(1, index), Some(Banners::banner(banner_id)));
+            }
+            assert_eq!(Banners::owned_banner_at(1, 2), None);
+            assert_eq!(Banners::owned_banner_at(2, 0), None);
+        });
+    }
+
+    /// The `AuctionStateChanged` transitions deposited for `banner_id`, in order.
+    fn auction_states(banner_id: H256) -> Vec<AuctionState> {
+        banner_events().into_iter().filter_map(|event| match event {
+            RawEvent::AuctionStateChanged(id, state) if id == banner_id => Some(state),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn auction_transitions_emit_aggregate_event() {
+        with_externalities(&mut new_test_ext(), || {
+            let sold = create(1);
+            let unsold = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unsold, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 20));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(3), sold, 0));
+            assert_ok!(Banners::bid(Origin::signed(3), unsold, 0));
+
+            assert_eq!(auction_states(sold), vec![AuctionState::Started, AuctionState::Bid, AuctionState::Ended]);
+            assert_eq!(auction_states(unsold), vec![AuctionState::Started, AuctionState::Aborted]);
+        });
+    }
+
+    #[test]
+    fn settle_expired_skips_live_auctions() {
+        with_externalities(&mut new_test_ext(), || {
+            let sold = create(1);
+            let unsold = create(1);
+            let live = create(1);
+            let idle = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unsold, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 20));
+
+            System::set_block_number(10);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), live, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![sold, unsold, live, idle, H256::zero()]));
+
+            assert_eq!(Banners::owner_of(sold), Some(2));
+            assert!(!Banners::banner(sold).can_bid);
+            assert_eq!(Banners::owner_of(unsold), Some(1));
+            assert!(!Banners::banner(unsold).can_bid);
+            assert!(Banners::banner(live).can_bid);
+
+            let events = banner_events();
+            assert!(events.contains(&RawEvent::Deal(2, sold, 20)));
+            assert!(events.contains(&RawEvent::Abort(1, unsold)));
+        });
+    }
+
+    #[test]
+    fn real_bid_flag_decides_deal_or_abort() {
+        with_externalities(&mut new_test_ext(), || {
+            let unsold = create(1);
+            let sold = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unsold, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert!(!Banners::banner(sold).has_real_bid);
+
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 20));
+            assert!(Banners::banner(sold).has_real_bid);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![unsold, sold]));
+
+            let events = banner_events();
+            assert!(events.contains(&RawEvent::Abort(1, unsold)));
+            assert!(events.contains(&RawEvent::Deal(2, sold, 20)));
+            assert!(!Banners::banner(sold).has_real_bid);
+        });
+    }
+
+    #[test]
+    fn real_bid_by_the_owner_settles_as_a_deal() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            // Simulate the owner ending up recorded as the high bidder after a real bid.
+            <Banners<Test>>::mutate(banner_id, |banner| banner.current_bidder = 1);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionStateChanged(banner_id, AuctionState::Ended)));
+            assert!(banner_events().contains(&RawEvent::Deal(1, banner_id, 20)));
+        });
+    }
+
+    #[test]
+    fn provenance_records_mint_and_transfers_in_order() {
+        with_externalities(&mut new_test_ext(), || {
+            System::set_block_number(3);
+            let banner_id = create(1);
+
+            System::set_block_number(7);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 20));
+
+            System::set_block_number(12);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+
+            assert_eq!(Banners::provenance(banner_id), vec![(1, 3), (2, 7), (3, 12)]);
+
+            assert_ok!(Banners::burn_banner(Origin::signed(3), banner_id));
+            assert!(Banners::provenance(banner_id).is_empty());
+        });
+    }
+
+    #[test]
+    fn provenance_drops_oldest_entries() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            for n in 1..MAX_HISTORY as u64 + 1 {
+                System::set_block_number(n);
+                let (from, to) = if n % 2 == 1 { (1, 2) } else { (2, 1) };
+                assert_ok!(Banners::transfer_from(from, to, banner_id));
+            }
+
+            let history = Banners::provenance(banner_id);
+            assert_eq!(history.len(), MAX_HISTORY);
+            assert_eq!(history[0], (2, 1));
+            assert_eq!(history[MAX_HISTORY - 1], (1, MAX_HISTORY as u64));
+        });
+    }
+
+    #[test]
+    fn winner_can_claim_an_ended_auction() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(2).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+
+            assert_noop!(Banners::claim(Origin::signed(3), banner_id), "This auction can't be settled yet");
+            System::set_block_number(AUCTION_DURATION);
+            assert_noop!(Banners::claim(Origin::signed(3), banner_id), "This auction can't be settled yet");
+
+            System::set_block_number(AUCTION_DURATION + 2);
+            assert_noop!(Banners::claim(Origin::signed(2), banner_id), "Only the winning bidder can claim this banner");
+            assert_noop!(Banners::claim(Origin::signed(1), banner_id), "Only the winning bidder can claim this banner");
+
+            assert_ok!(Banners::claim(Origin::signed(3), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert!(banner_events().contains(&RawEvent::Deal(3, banner_id, 30)));
+            assert_noop!(Banners::claim(Origin::signed(3), banner_id), "This banner is not on auction");
+        });
+    }
+
+    #[test]
+    fn unsold_auctions_cannot_be_claimed() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_noop!(Banners::claim(Origin::signed(1), banner_id), "Only the winning bidder can claim this banner");
+        });
+    }
+
+    #[test]
+    fn active_auctions_report_blocks_remaining() {
+        with_externalities(&mut new_test_ext(), || {
+            let long = create(1);
+            let short = create(1);
+            let idle = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), long, 10, vec![], None, None, None));
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, short, 10, 5));
+            assert_ok!(Banners::bid(Origin::signed(3), long, 20));
+
+            System::set_block_number(3);
+            assert_eq!(Banners::active_auctions_detailed(0, 10), vec![
+                AuctionView {
+                    banner_id: long,
+                    current_price: 20,
+                    current_bidder: Some(3),
+                    bid_end_height: AUCTION_DURATION,
+                    blocks_remaining: AUCTION_DURATION - 3,
+                },
+                AuctionView {
+                    banner_id: short,
+                    current_price: 10,
+                    current_bidder: None,
+                    bid_end_height: 5,
+                    blocks_remaining: 2,
+                },
+            ]);
+            assert!(!Banners::active_auctions_detailed(0, 10).iter().any(|view| view.banner_id == idle));
+
+            System::set_block_number(7);
+            assert_eq!(Banners::active_auctions_detailed(1, 10)[0].blocks_remaining, 0);
+
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![short]));
+            assert_eq!(Banners::active_auctions_count(), 1);
+            assert_eq!(Banners::active_auctions_detailed(0, 10).len(), 1);
+            assert!(Banners::active_auctions_detailed(1, 10).is_empty());
+        });
+    }
+
+    #[test]
+    fn active_auctions_are_paginated() {
+        with_externalities(&mut new_test_ext(), || {
+            let mut listed = vec![];
+            for _ in 0..3 {
+                let banner_id = create(1);
+                assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+                listed.push(banner_id);
+            }
+
+            let first_page: Vec<_> = Banners::active_auctions_detailed(0, 2).into_iter().map(|view| view.banner_id).collect();
+            let second_page: Vec<_> = Banners::active_auctions_detailed(2, 2).into_iter().map(|view| view.banner_id).collect();
+            assert_eq!(first_page, listed[..2].to_vec());
+            assert_eq!(second_page, listed[2..].to_vec());
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(2), vec![listed[0]]));
+            let remaining: Vec<_> = Banners::active_auctions_detailed(0, 10).into_iter().map(|view| view.banner_id).collect();
+            assert_eq!(remaining, vec![listed[2], listed[1]]);
+        });
+    }
+
+    #[test]
+    fn migrate_account_moves_every_banner() {
+        with_externalities(&mut new_test_ext(), || {
+            let idle = create(1);
+            let unbid = create(1);
+            let bid_on = create(1);
+            let kept = create(3);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unbid, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), bid_on, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), bid_on, 20));
+
+            assert_noop!(Banners::migrate_account(Origin::signed(1), 1, 3), "bad origin: expected to be a root origin");
+            assert_ok!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 3));
+
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::owned_banner_count(3), 4);
+            for &banner_id in [idle, unbid, bid_on, kept].iter() {
+                assert_eq!(Banners::owner_of(banner_id), Some(3));
+            }
+            assert_eq!(Banners::collection_stats(), (4, 1));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AccountMigrated(1, 3, 0)));
+
+            // The bid-less auction was aborted; the other keeps running for its bidder.
+            assert!(!Banners::banner(unbid).can_bid);
+            assert!(banner_events().contains(&RawEvent::Abort(1, unbid)));
+            assert_eq!(Banners::active_auction_count(1), 0);
+            assert_eq!(Banners::active_auction_count(3), 1);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![bid_on]));
+            assert_eq!(Banners::owner_of(bid_on), Some(2));
+            assert_eq!(Banners::active_auction_count(3), 0);
+        });
+    }
+
+    #[test]
+    fn migrate_account_keeps_transfer_checks() {
+        with_externalities(&mut ExtBuilder::default().installments(10, 5).build(), || {
+            let idle = create(1);
+            let sold = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 100));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::claim(Origin::signed(2), sold));
+
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 4, true));
+            assert_noop!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 4), "account blocked");
+            assert_noop!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 3), "This banner is awaiting payment");
+            assert_eq!(Banners::owner_of(idle), Some(1));
+
+            assert_ok!(Banners::finalize_payment(Origin::signed(2), sold));
+            assert_ok!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 3));
+            assert_eq!(Banners::owner_of(idle), Some(3));
+
+            assert_ok!(Banners::retire(Origin::signed(3), idle));
+            assert_noop!(Banners::migrate_account(system::RawOrigin::Root.into(), BURN_ADDRESS, 3), "This banner has been retired");
+        });
+    }
+
+    #[test]
+    fn migrate_account_continues_across_calls() {
+        with_externalities(&mut new_test_ext(), || {
+            for _ in 0..MAX_MIGRATE_BATCH + 2 {
+                create(1);
+            }
+
+            assert_ok!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 2));
+            assert_eq!(Banners::owned_banner_count(1), 2);
+            assert_eq!(Banners::owned_banner_count(2), MAX_MIGRATE_BATCH);
+            assert_eq!(banner_events().last(), Some(&RawEvent::AccountMigrated(1, 2, 2)));
+
+            assert_ok!(Banners::migrate_account(system::RawOrigin::Root.into(), 1, 2));
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::owned_banner_count(2), MAX_MIGRATE_BATCH + 2);
+            assert_eq!(Banners::collection_stats(), (MAX_MIGRATE_BATCH + 2, 1));
+        });
+    }
+
+    #[test]
+    fn retired_banners_keep_their_data_but_never_move() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let banner = Banners::banner(banner_id);
+            assert_noop!(Banners::retire(Origin::signed(2), banner_id), "You do not own this banner");
+
+            assert_ok!(Banners::retire(Origin::signed(1), banner_id));
+            assert_eq!(banner_events().last(), Some(&RawEvent::Retired(1, banner_id)));
+            assert!(Banners::is_retired(banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(BURN_ADDRESS));
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::banner(banner_id), banner);
+            assert_eq!(Banners::creator_of(banner_id), Some(1));
+            assert_eq!(Banners::provenance(banner_id).iter().map(|entry| entry.0).collect::<Vec<_>>(), vec![1, BURN_ADDRESS]);
+
+            assert_noop!(Banners::gift(Origin::signed(BURN_ADDRESS), 2, banner_id, vec![]), "This banner has been retired");
+            assert_noop!(
+                <Banners as Nft<u64>>::transfer(&BURN_ADDRESS, &2, &banner_id),
+                "This banner has been retired"
+            );
+        });
+    }
+
+    #[test]
+    fn try_state_flags_corrupted_indexes() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            create(1);
+            create(2);
+
+            <AllBannersCount<Test>>::put(2);
+            assert_eq!(Banners::do_try_state(), Err("AllBannersArray has entries past AllBannersCount"));
+            <AllBannersCount<Test>>::put(3);
+
+            <AllBannersIndex<Test>>::insert(first, 1);
+            assert_eq!(Banners::do_try_state(), Err("AllBannersIndex doesn't point back to AllBannersArray"));
+            <AllBannersIndex<Test>>::insert(first, 0);
+
+            <OwnedBannersIndex<Test>>::insert(first, 1);
+            assert_eq!(Banners::do_try_state(), Err("OwnedBannersIndex doesn't point back to OwnedBannersArray"));
+            <OwnedBannersIndex<Test>>::insert(first, 0);
+
+            <OwnedBannersCount<Test>>::insert(1, 3);
+            assert_eq!(Banners::do_try_state(), Err("OwnedBannersCount doesn't add up to AllBannersCount"));
+            <OwnedBannersCount<Test>>::insert(1, 1);
+            assert_eq!(Banners::do_try_state(), Err("OwnedBannersIndex is past the owner's OwnedBannersCount"));
+            <OwnedBannersCount<Test>>::insert(1, 2);
+
+            <BannerOwner<Test>>::insert(first, 2);
+            assert_eq!(Banners::do_try_state(), Err("OwnedBannersIndex doesn't point back to OwnedBannersArray"));
+            <BannerOwner<Test>>::insert(first, 1);
+
+            assert_ok!(Banners::do_try_state());
+        });
+    }
+
+    #[test]
+    fn owner_enumeration_stays_consistent_after_many_transfers() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_ids: Vec<H256> = (0..6).map(|n| create(n % 3 + 1)).collect();
+            for round in 0..20u64 {
+                let banner_id = banner_ids[(round * 7 % 6) as usize];
+                let from = Banners::owner_of(banner_id).unwrap();
+                let to = (from + round) % 3 + 1;
+                if to != from {
+                    assert_ok!(Banners::gift(Origin::signed(from), to, banner_id, vec![]));
+                }
+            }
+
+            for &who in &[1, 2, 3] {
+                let count = Banners::owned_banner_count(who);
+                let mut enumerated: Vec<H256> = (0..count).map(|i| Banners::banner_of_owner_by_index((who, i))).collect();
+                for (i, banner_id) in enumerated.iter().enumerate() {
+                    assert_eq!(<OwnedBannersIndex<Test>>::get(*banner_id), i as u64);
+                }
+                assert!(!<OwnedBannersArray<Test>>::exists((who, count)));
+
+                let mut owned: Vec<H256> = banner_ids.iter().cloned().filter(|id| Banners::owner_of(*id) == Some(who)).collect();
+                enumerated.sort();
+                owned.sort();
+                assert_eq!(enumerated, owned);
+            }
+            assert_ok!(Banners::do_try_state());
+        });
+    }
+
+    #[test]
+    fn bidding_off_auction_says_why() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 20), "banner is not on auction");
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 20), "auction has ended");
+        });
+    }
+
+    #[test]
+    fn created_count_survives_transfers_and_burns() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_eq!(Banners::created_count(1), 0);
+            let gifted = create(1);
+            let burned = create(1);
+            assert_eq!(Banners::created_count(1), 2);
+
+            assert_ok!(Banners::gift(Origin::signed(1), 2, gifted, vec![]));
+            assert_ok!(Banners::burn_banner(Origin::signed(1), burned));
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::created_count(1), 2);
+            assert_eq!(Banners::created_count(2), 0);
+        });
+    }
+
+    #[test]
+    fn gifting_moves_the_banner_with_a_message() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::gift(Origin::signed(2), 3, banner_id, b"hi".to_vec()), "You do not own this banner");
+            assert_noop!(
+                Banners::gift(Origin::signed(1), 3, banner_id, vec![b'a'; MAX_GIFT_MESSAGE_LENGTH + 1]),
+                "Gift message is too long"
+            );
+
+            assert_ok!(Banners::gift(Origin::signed(1), 3, banner_id, b"happy birthday".to_vec()));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(banner_events().last(), Some(&RawEvent::Gifted(1, 3, banner_id, b"happy birthday".to_vec())));
+
+            assert_ok!(Banners::auction_banner(Origin::signed(3), banner_id, 10, vec![], None, None, None));
+            assert_noop!(Banners::gift(Origin::signed(3), 1, banner_id, vec![]), "cannot transfer during auction");
+        });
+    }
+
+    #[test]
+    fn settlement_hook_sees_deals_and_aborts() {
+        with_externalities(&mut new_test_ext(), || {
+            let unsold = create(1);
+            let sold = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unsold, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 20));
+            assert!(settled_auctions().is_empty());
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![unsold, sold]));
+            assert_eq!(settled_auctions(), vec![
+                (unsold, 1, None, 0, AuctionState::Aborted),
+                (sold, 1, Some(2), 20, AuctionState::Ended),
+            ]);
+        });
+    }
+
+    #[test]
+    fn root_verifies_banners_and_it_survives_transfers() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::set_verified(Origin::signed(1), banner_id, true), "bad origin: expected to be a root origin");
+            assert_noop!(Banners::set_verified(system::RawOrigin::Root.into(), H256::zero(), true), "This banner does not exist");
+            assert!(!Banners::banner_detail(banner_id).unwrap().verified);
+
+            assert_ok!(Banners::set_verified(system::RawOrigin::Root.into(), banner_id, true));
+            assert_eq!(banner_events().last(), Some(&RawEvent::VerificationChanged(banner_id, true)));
+            assert!(Banners::banner_detail(banner_id).unwrap().verified);
+
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+            assert_ok!(Banners::buy_fixed(Origin::signed(2), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert!(Banners::banner_detail(banner_id).unwrap().verified);
+
+            assert_ok!(Banners::set_verified(system::RawOrigin::Root.into(), banner_id, false));
+            assert_eq!(banner_events().last(), Some(&RawEvent::VerificationChanged(banner_id, false)));
+            assert!(!Banners::is_verified(banner_id));
+        });
+    }
+
+    #[test]
+    fn translations_are_capped_per_banner() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            for n in 0..MAX_LANGUAGES as u8 {
+                assert_ok!(Banners::set_localized_metadata(Origin::signed(1), banner_id, [b'a', b'a' + n], b"name".to_vec(), b"d".to_vec()));
+            }
+            assert_eq!(Banners::localized_languages(banner_id).len(), MAX_LANGUAGES);
+
+            assert_noop!(
+                Banners::set_localized_metadata(Origin::signed(1), banner_id, *b"zz", b"name".to_vec(), b"d".to_vec()),
+                "Too many translations for this banner"
+            );
+            // Updating an existing translation doesn't need a new slot.
+            assert_ok!(Banners::set_localized_metadata(Origin::signed(1), banner_id, *b"aa", b"other".to_vec(), b"d".to_vec()));
+        });
+    }
+
+    #[test]
+    fn image_url_index_is_capped_per_url() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_ids: Vec<H256> = (0..MAX_BANNERS_PER_IMAGE + 1).map(|_| create(1)).collect();
+            assert_eq!(Banners::banners_with_image(b"ipfs://url".to_vec()), banner_ids[..MAX_BANNERS_PER_IMAGE].to_vec());
+        });
+    }
+
+    #[test]
+    fn localized_metadata_falls_back_to_the_banner_itself() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let base = (Banners::banner(banner_id).name, b"desc".to_vec());
+            assert_eq!(Banners::localized_metadata(banner_id, *b"fr"), base);
+
+            assert_noop!(
+                Banners::set_localized_metadata(Origin::signed(2), banner_id, *b"fr", b"banniere".to_vec(), b"d".to_vec()),
+                "You do not own this banner"
+            );
+            assert_noop!(
+                Banners::set_localized_metadata(Origin::signed(1), banner_id, *b"fr", vec![], b"d".to_vec()),
+                "Banner name must not be empty"
+            );
+            assert_noop!(
+                Banners::set_localized_metadata(Origin::signed(1), banner_id, *b"fr", b"banniere".to_vec(), vec![b'a'; MAX_DESC_LENGTH + 1]),
+                "Banner description is too long"
+            );
+
+            assert_ok!(Banners::set_localized_metadata(Origin::signed(1), banner_id, *b"fr", b"banniere".to_vec(), b"d".to_vec()));
+            assert_eq!(Banners::localized_metadata(banner_id, *b"fr"), (b"banniere".to_vec(), b"d".to_vec()));
+            assert_eq!(Banners::localized_metadata(banner_id, *b"de"), base);
+
+            assert_ok!(Banners::burn_banner(Origin::signed(1), banner_id));
+            assert_eq!(<LocalizedMetadata<Test>>::get((banner_id, *b"fr")), None);
+            assert!(Banners::localized_languages(banner_id).is_empty());
+        });
+    }
+
+    #[test]
+    fn content_hash_follows_the_image_url() {
+        with_externalities(&mut new_test_ext(), || {
+            let original = H256::from([1; 32]);
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"hashed".to_vec(), b"ipfs://a".to_vec(), b"desc".to_vec(), original));
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_eq!(Banners::banner_detail(banner_id).unwrap().banner.content_hash, original);
+
+            let updated = H256::from([2; 32]);
+            assert_ok!(Banners::set_image_url(Origin::signed(1), banner_id, b"ipfs://b".to_vec(), updated));
+            let banner = Banners::banner_detail(banner_id).unwrap().banner;
+            assert_eq!(banner.image_url, b"ipfs://b".to_vec());
+            assert_eq!(banner.content_hash, updated);
+        });
+    }
+
+    #[test]
+    fn banners_by_ids_returns_each_requested_banner_in_order() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            let second = create(2);
+            let missing = H256::zero();
+
+            assert_eq!(Banners::banners_by_ids(vec![second, missing, first]), vec![
+                (second, Some(Banners::banner(second))),
+                (missing, None),
+                (first, Some(Banners::banner(first))),
+            ]);
+            assert_eq!(Banners::banners_by_ids(vec![missing; MAX_BANNERS_BY_IDS + 1]).len(), MAX_BANNERS_BY_IDS);
+        });
+    }
+
+    #[test]
+    fn banners_page_cursor_survives_minting_between_pages() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            let second = create(2);
+            let third = create(1);
+
+            let (page, cursor) = Banners::banners_page(None, 2);
+            assert_eq!(page, vec![first, second]);
+            assert_eq!(cursor, Some(2));
+
+            let fourth = create(2);
+            let (page, cursor) = Banners::banners_page(cursor, 2);
+            assert_eq!(page, vec![third, fourth]);
+            assert_eq!(cursor, None);
+
+            let (page, cursor) = Banners::banners_of(1, None, 1);
+            assert_eq!(page, vec![first]);
+            let fifth = create(1);
+            let (page, cursor) = Banners::banners_of(1, cursor, 10);
+            assert_eq!(page, vec![third, fifth]);
+            assert_eq!(cursor, None);
+
+            assert_eq!(Banners::banners_of(3, None, 10), (vec![], None));
+        });
+    }
+
+    #[test]
+    fn supply_is_sampled_at_the_interval_and_pruned() {
+        with_externalities(&mut ExtBuilder::default().supply_sampling(10, 2).build(), || {
+            for block in 1..=30 {
+                System::set_block_number(block);
+                if block % 5 == 0 {
+                    create(1);
+                }
+                <Banners as OnFinalize<u64>>::on_finalize(block);
+            }
+
+            assert_eq!(Banners::supply_history(9), None);
+            assert_eq!(Banners::supply_history(10), None);
+            assert_eq!(Banners::supply_history(20), Some(4));
+            assert_eq!(Banners::supply_history(30), Some(6));
+
+            assert_eq!(Banners::supply_at(25), Some(4));
+            assert_eq!(Banners::supply_at(30), Some(6));
+            assert_eq!(Banners::supply_at(15), None);
+        });
+    }
+
+    #[test]
+    fn zero_supply_interval_takes_no_samples() {
+        with_externalities(&mut new_test_ext(), || {
+            create(1);
+            <Banners as OnFinalize<u64>>::on_finalize(10);
+            assert_eq!(Banners::supply_history(10), None);
+            assert_eq!(Banners::supply_at(10), None);
+        });
+    }
+
+    #[test]
+    fn banners_with_image_lists_every_banner_sharing_a_url() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            let second = create(2);
+            let url = b"ipfs://url".to_vec();
+            assert_eq!(Banners::banners_with_image(url.clone()), vec![first, second]);
+
+            assert_ok!(Banners::set_image_url(Origin::signed(1), first, b"ipfs://other".to_vec(), H256::zero()));
+            assert_eq!(Banners::banners_with_image(url.clone()), vec![second]);
+            assert_eq!(Banners::banners_with_image(b"ipfs://other".to_vec()), vec![first]);
+
+            assert_ok!(Banners::update_metadata(Origin::signed(2), second, None, Some(b"ipfs://other".to_vec()), None));
+            assert!(Banners::banners_with_image(url).is_empty());
+            assert_eq!(Banners::banners_with_image(b"ipfs://other".to_vec()), vec![first, second]);
+        });
+    }
+
+    #[test]
+    fn banner_detail_bundles_individual_getters() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_eq!(Banners::banner_detail(H256::zero()), None);
+
+            let banner_id = create(1);
+            let detail = Banners::banner_detail(banner_id).unwrap();
+            assert_eq!(detail.banner, Banners::banner(banner_id));
+            assert_eq!(detail.owner, 1);
+            assert_eq!(detail.auction, None);
+            assert_eq!(detail.blocks_remaining, 0);
+            assert_eq!(detail.last_sale_price, None);
+            assert!(!detail.verified);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            System::set_block_number(4);
+            let detail = Banners::banner_detail(banner_id).unwrap();
+            assert_eq!(detail.banner, Banners::banner(banner_id));
+            assert_eq!(detail.auction, Banners::auction_status(banner_id));
+            assert_eq!(detail.blocks_remaining, AUCTION_DURATION - 4);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            let detail = Banners::banner_detail(banner_id).unwrap();
+            assert_eq!(detail.owner, 2);
+            assert_eq!(detail.auction, None);
+            assert_eq!(detail.last_sale_price, Some(20));
+            assert_eq!(detail.last_sale_price, Banners::last_sale_price(banner_id));
+
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 50));
+            assert_ok!(Banners::accept_offer(Origin::signed(2), banner_id, 3));
+            assert_eq!(Banners::banner_detail(banner_id).unwrap().last_sale_price, Some(50));
+        });
+    }
+
+    #[test]
+    fn settle_expired_is_bounded() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_noop!(Banners::settle_expired(Origin::signed(1), vec![H256::zero(); MAX_SETTLE_BATCH + 1]),
+                "Too many banners to settle in one call");
+        });
+    }
+
+    #[test]
+    fn current_bidder_of_ignores_owner_placeholder() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::current_bidder_of(banner_id), None);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Banners::current_bidder_of(banner_id), None);
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_eq!(Banners::current_bidder_of(banner_id), Some(2));
+        });
+    }
+
+    #[test]
+    fn image_urls_must_use_an_allowed_scheme() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"ipfs".to_vec(), b"ipfs://Qm".to_vec(), b"desc".to_vec(), H256::zero()));
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"https".to_vec(), b"https://a.io/b.png".to_vec(), b"desc".to_vec(), H256::zero()));
+            assert_noop!(
+                Banners::create_banner(Origin::signed(1), b"js".to_vec(), b"javascript:alert(1)".to_vec(), b"desc".to_vec(), H256::zero()),
+                "unsupported url scheme"
+            );
+
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_noop!(Banners::set_image_url(Origin::signed(1), banner_id, b"data:image/png;base64,AA".to_vec(), H256::zero()), "unsupported url scheme");
+            assert_ok!(Banners::set_image_url(Origin::signed(1), banner_id, b"https://a.io/c.png".to_vec(), H256::zero()));
+        });
+    }
+
+    #[test]
+    fn urls_and_descriptions_are_bounded() {
+        with_externalities(&mut new_test_ext(), || {
+            let long_url = |len: usize| {
+                let mut url = b"ipfs://".to_vec();
+                url.resize(len, b'a');
+                url
+            };
+
+            assert_noop!(
+                Banners::create_banner(Origin::signed(1), b"long".to_vec(), long_url(MAX_URL_LENGTH + 1), b"desc".to_vec(), H256::zero()),
+                "Image url is too long"
+            );
+            assert_noop!(
+                Banners::create_banner(Origin::signed(1), b"long".to_vec(), b"ipfs://url".to_vec(), vec![b'a'; MAX_DESC_LENGTH + 1], H256::zero()),
+                "Banner description is too long"
+            );
+            assert_ok!(Banners::create_banner(Origin::signed(1), b"long".to_vec(), long_url(MAX_URL_LENGTH), vec![b'a'; MAX_DESC_LENGTH], H256::zero()));
+
+            let banner_id = Banners::banner_of_owner_by_index((1, 0));
+            assert_noop!(Banners::set_image_url(Origin::signed(1), banner_id, long_url(MAX_URL_LENGTH + 1), H256::zero()), "Image url is too long");
+            assert_noop!(Banners::update_metadata(Origin::signed(1), banner_id, None, None, Some(vec![b'a'; MAX_DESC_LENGTH + 1])),
+                "Banner description is too long");
+        });
+    }
+
+    #[test]
+    fn active_auctions_are_capped_per_account() {
+        with_externalities(&mut ExtBuilder::default().max_active_auctions_per_account(2).build(), || {
+            let first = create(1);
+            let second = create(1);
+            let third = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), first, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), second, 10, vec![], None, None, None));
+            assert_eq!(Banners::active_auction_count(1), 2);
+
+            assert_noop!(Banners::auction_banner(Origin::signed(1), third, 10, vec![], None, None, None), "Too many active auctions for this account");
+            let gift = create(2);
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(2), 1, gift, 10, 5), "Too many active auctions for this account");
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(2), vec![first]));
+            assert_eq!(Banners::active_auction_count(1), 1);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), third, 10, vec![], None, None, None));
+            assert_eq!(Banners::active_auction_count(1), 2);
+        });
+    }
+
+    #[test]
+    fn zero_auction_limit_is_uncapped() {
+        with_externalities(&mut new_test_ext(), || {
+            for _ in 0..3 {
+                let banner_id = create(1);
+                assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            }
+            assert_eq!(Banners::active_auction_count(1), 3);
+        });
+    }
+
+    #[test]
+    fn offer_lifecycle_reserves_and_settles_funds() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 50));
+            assert_eq!(Banners::offer((banner_id, 2)), 100);
+            assert_eq!(Balances::reserved_balance(&2), 100);
+            assert_eq!(Balances::free_balance(&2), 900);
+
+            assert_ok!(Banners::withdraw_offer(Origin::signed(2), banner_id));
+            assert!(!<Offers<Test>>::exists((banner_id, 2)));
+            assert_eq!(Balances::reserved_balance(&2), 0);
+            assert_eq!(Balances::free_balance(&2), 1000);
+            assert_eq!(banner_events().last(), Some(&RawEvent::OfferWithdrawn(2, banner_id)));
+
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 200));
+            assert_ok!(Banners::accept_offer(Origin::signed(1), banner_id, 2));
+
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert!(!<Offers<Test>>::exists((banner_id, 2)));
+            assert_eq!(Balances::reserved_balance(&2), 0);
+            assert_eq!(Balances::free_balance(&2), 800);
+            assert_eq!(Balances::free_balance(&1), 1200);
+            assert_eq!(banner_events().last(), Some(&RawEvent::OfferAccepted(1, 2, banner_id, 200)));
+
+            // The other offer is still outstanding and can be withdrawn.
+            assert_eq!(Balances::reserved_balance(&3), 50);
+            assert_ok!(Banners::withdraw_offer(Origin::signed(3), banner_id));
+            assert_eq!(Balances::free_balance(&3), 1000);
+        });
+    }
+
+    #[test]
+    fn offers_are_rejected_while_on_auction() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::make_offer(Origin::signed(3), banner_id, 100), "This banner is on auction");
+            assert_noop!(Banners::accept_offer(Origin::signed(1), banner_id, 2), "This banner is on auction");
+        });
+    }
+
+    #[test]
+    fn offers_per_banner_are_capped() {
+        with_externalities(&mut ExtBuilder::default().max_offers_per_banner(2).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 10));
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 10));
+            assert_eq!(Banners::offer_count(banner_id), 2);
+
+            assert_noop!(Banners::make_offer(Origin::signed(4), banner_id, 10), "This banner has too many outstanding offers");
+
+            assert_ok!(Banners::withdraw_offer(Origin::signed(2), banner_id));
+            assert_ok!(Banners::make_offer(Origin::signed(4), banner_id, 10));
+
+            assert_ok!(Banners::accept_offer(Origin::signed(1), banner_id, 3));
+            assert_eq!(Banners::offer_count(banner_id), 1);
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 10));
+            assert_eq!(Banners::offer_count(banner_id), 2);
+        });
+    }
+
+    #[test]
+    fn counter_helpers_fail_instead_of_wrapping() {
+        assert_eq!(Banners::increment(1u64, "overflow"), Ok(2));
+        assert_eq!(Banners::increment(u32::max_value(), "overflow"), Err("overflow"));
+        assert_eq!(Banners::decrement(1u32, "underflow"), Ok(0));
+        assert_eq!(Banners::decrement(0u64, "underflow"), Err("underflow"));
+    }
+
+    #[test]
+    fn burning_the_last_banner_empties_every_counter() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::burn_banner(Origin::signed(1), banner_id));
+
+            assert_eq!(Banners::owned_banner_count(1), 0);
+            assert_eq!(Banners::collection_stats(), (0, 0));
+        });
+    }
+
+    #[test]
+    fn corrupted_counters_fail_without_panicking() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+
+            <OwnedBannersCount<Test>>::insert(1, 0);
+            assert_noop!(Banners::burn_banner(Origin::signed(1), banner_id), "Burn causes underflow of owner banner balance");
+            assert_noop!(Banners::transfer_from(1, 2, banner_id), "Transfer causes underflow of 'from' banner balance");
+
+            <OwnedBannersCount<Test>>::insert(1, 1);
+            <AllBannersCount<Test>>::put(0);
+            assert_noop!(Banners::burn_banner(Origin::signed(1), banner_id), "Burn causes underflow of total supply");
+
+            <AllBannersCount<Test>>::put(1);
+            <DistinctOwnerCount<Test>>::put(0);
+            assert_noop!(Banners::burn_banner(Origin::signed(1), banner_id), "Burn causes underflow of banner owners");
+
+            <AllBannersCount<Test>>::put(u64::max_value());
+            assert_noop!(
+                Banners::create_banner(Origin::signed(2), b"overflow".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()),
+                "Overflow adding a new banner to total supply"
+            );
+        });
+    }
+
+    #[test]
+    fn fixed_price_sale_flow() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(5).build(), || {
+            let banner_id = create(1);
+
+            assert_noop!(Banners::list_fixed_price(Origin::signed(2), banner_id, 100), "You do not own this banner");
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+            assert_eq!(Banners::fixed_price(banner_id), Some(100));
+            assert_eq!(banner_events().last(), Some(&RawEvent::Listed(1, banner_id, 100)));
+
+            assert_noop!(Banners::buy_fixed(Origin::signed(1), banner_id), "You can't buy your own banner");
+            assert_ok!(Banners::buy_fixed(Origin::signed(2), banner_id));
+
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Banners::fixed_price(banner_id), None);
+            assert_eq!(Banners::last_sale_price(banner_id), Some(100));
+            assert_eq!(Balances::free_balance(&1), 1095);
+            assert_eq!(Balances::free_balance(&2), 900);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 5);
+            assert_eq!(banner_events().last(), Some(&RawEvent::Sold(2, banner_id, 100)));
+
+            assert_noop!(Banners::buy_fixed(Origin::signed(3), banner_id), "This banner is not listed for sale");
+        });
+    }
+
+    #[test]
+    fn delisting_and_transfers_clear_fixed_price() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+
+            assert_noop!(Banners::delist(Origin::signed(2), banner_id), "You do not own this banner");
+            assert_ok!(Banners::delist(Origin::signed(1), banner_id));
+            assert_eq!(Banners::fixed_price(banner_id), None);
+            assert_eq!(banner_events().last(), Some(&RawEvent::Delisted(1, banner_id)));
+            assert_noop!(Banners::buy_fixed(Origin::signed(2), banner_id), "This banner is not listed for sale");
+
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 50));
+            assert_ok!(Banners::accept_offer(Origin::signed(1), banner_id, 3));
+            assert_eq!(Banners::fixed_price(banner_id), None);
+        });
+    }
+
+    #[test]
+    fn fixed_price_listings_and_auctions_exclude_each_other() {
+        with_externalities(&mut new_test_ext(), || {
+            let listed = create(1);
+            let auctioned = create(1);
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), listed, 100));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), auctioned, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::auction_banner(Origin::signed(1), listed, 10, vec![], None, None, None), "This banner is listed for sale");
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 2, listed, 10, 5), "This banner is listed for sale");
+            assert_noop!(Banners::list_fixed_price(Origin::signed(1), auctioned, 100), "This banner is on auction");
+        });
+    }
+
+    #[test]
+    fn conversion_helpers() {
+        assert_eq!(Banners::zero_balance(), 0);
+        assert_eq!(Banners::blocks(0), 0);
+        assert_eq!(Banners::blocks(AUCTION_DURATION), AUCTION_DURATION);
+    }
+
+    #[test]
+    fn bid_increments_follow_price_tiers() {
+        with_externalities(&mut ExtBuilder::default().bid_increment_tiers(vec![(0, 1), (100, 5), (1000, 50)]).build(), || {
+            assert_eq!(Banners::min_next_bid(0), 1);
+            assert_eq!(Banners::min_next_bid(99), 100);
+            assert_eq!(Banners::min_next_bid(100), 105);
+            assert_eq!(Banners::min_next_bid(999), 1004);
+            assert_eq!(Banners::min_next_bid(1000), 1050);
+            assert_eq!(Banners::min_next_bid(5000), 5050);
+
+            let low = create(1);
+            let high = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), low, 99, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), high, 100, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), low, 100));
+            assert_noop!(Banners::bid(Origin::signed(3), low, 104), "Bid is below the minimum increment");
+            assert_ok!(Banners::bid(Origin::signed(3), low, 105));
+
+            assert_noop!(Banners::bid(Origin::signed(2), high, 104), "Bid is below the minimum increment");
+            assert_ok!(Banners::bid(Origin::signed(2), high, 105));
+        });
+    }
+
+    #[test]
+    fn bid_increment_tiers_are_validated() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_eq!(Banners::min_next_bid(500), 500);
+
+            assert_noop!(Banners::set_bid_increment_tiers(system::RawOrigin::Root.into(), vec![(100, 5), (0, 1)]),
+                "Bid increment tiers must be in ascending threshold order");
+            assert_noop!(Banners::set_bid_increment_tiers(system::RawOrigin::Root.into(), (0..MAX_INCREMENT_TIERS as u64 + 1).map(|n| (n, 1)).collect()),
+                "Too many bid increment tiers");
+            assert_noop!(Banners::set_bid_increment_tiers(Origin::signed(1), vec![(0, 1)]), "bad origin: expected to be a root origin");
+
+            assert_ok!(Banners::set_bid_increment_tiers(system::RawOrigin::Root.into(), vec![(0, 1), (100, 5)]));
+            assert_eq!(Banners::min_next_bid(500), 505);
+        });
+    }
+
+    #[test]
+    fn repeat_bids_wait_for_cooldown() {
+        with_externalities(&mut ExtBuilder::default().bid_cooldown(5).build(), || {
+            let banner_id = create(1);
+            let other = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), other, 10, vec![], None, None, None));
+
+            System::set_block_number(10);
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            // The cooldown is per banner.
+            assert_ok!(Banners::bid(Origin::signed(2), other, 20));
+
+            System::set_block_number(14);
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 40), "You are bidding too soon after your last bid");
+
+            System::set_block_number(15);
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 40));
+            assert_eq!(<LastBidBlock<Test>>::get(&banner_id, &2), Some(15));
+            assert_eq!(<LastBidBlock<Test>>::get(&banner_id, &3), Some(10));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+            assert_eq!(<LastBidBlock<Test>>::get(&banner_id, &2), None);
+            assert_eq!(<LastBidBlock<Test>>::get(&banner_id, &3), None);
+        });
+    }
+
+    #[test]
+    fn zero_cooldown_records_nothing() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 40));
+            assert_eq!(<LastBidBlock<Test>>::get(&banner_id, &2), None);
+        });
+    }
+
+    #[test]
+    fn bids_in_final_window_emit_late_bid() {
+        with_externalities(&mut ExtBuilder::default().anti_snipe_window(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION - 11);
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert!(!banner_events().iter().any(|event| match event {
+                RawEvent::LateBid(..) => true,
+                _ => false,
+            }));
+
+            System::set_block_number(AUCTION_DURATION - 10);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(banner_events().last(), Some(&RawEvent::LateBid(banner_id, 3, 10)));
+        });
+    }
+
+    #[test]
+    fn private_auctions_only_accept_invited_bidders() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![2], None, None, None));
+            assert_eq!(Banners::allowed_bidders(banner_id), vec![2]);
+
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 20), "not invited to this auction");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 0));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert!(Banners::allowed_bidders(banner_id).is_empty());
+        });
+    }
+
+    #[test]
+    fn invite_list_is_bounded() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![2; MAX_ALLOWED_BIDDERS + 1], None, None, None), "Too many invited bidders");
+        });
+    }
+
+    #[test]
+    fn unsold_auctions_relist_cheaper_until_the_count_runs_out() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 50, vec![], None, None, Some((2, 20))));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionStateChanged(banner_id, AuctionState::Started)));
+            assert!(banner_events().contains(&RawEvent::StartAuction(1, banner_id, 30)));
+            assert!(Banners::banner(banner_id).can_bid);
+            assert_eq!(Banners::auto_relist(banner_id), Some((1, 20)));
+
+            System::set_block_number(AUCTION_DURATION * 2);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert!(banner_events().contains(&RawEvent::StartAuction(1, banner_id, 10)));
+            assert_eq!(Banners::auto_relist(banner_id), None);
+
+            System::set_block_number(AUCTION_DURATION * 3);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionStateChanged(banner_id, AuctionState::Aborted)));
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert_eq!(Banners::active_auction_count(1), 0);
+        });
+    }
+
+    #[test]
+    fn auto_relist_stops_rather_than_underflow() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, Some((5, 11))));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert_eq!(Banners::auto_relist(banner_id), None);
+        });
+    }
+
+    #[test]
+    fn bids_must_fall_within_the_auction_limits() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(
+                Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, Some(10), None),
+                "Maximum bid must be above the starting price"
+            );
+            assert_noop!(
+                Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], Some(60), Some(50), None),
+                "Minimum bid can't exceed the maximum bid"
+            );
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], Some(20), Some(50), None));
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 19), "Bid is below this auction's minimum bid");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 51), "Bid is above this auction's maximum bid");
+            assert_eq!(Balances::free_balance(&3), 1000);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 50));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+            assert_eq!(Banners::bid_limits(banner_id), (None, None));
+        });
+    }
+
+    #[test]
+    fn reprice_stays_below_the_maximum_bid() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], Some(20), Some(50), None));
+
+            assert_noop!(Banners::reprice_auction(Origin::signed(1), banner_id, 50), "Maximum bid must be above the starting price");
+            assert_noop!(Banners::reprice_auction(Origin::signed(1), banner_id, 60), "Maximum bid must be above the starting price");
+            assert_ok!(Banners::reprice_auction(Origin::signed(1), banner_id, 30));
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 30), "your bid price must be greater than current price");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 50));
+        });
+    }
+
+    #[test]
+    fn auction_manager_runs_auctions_for_the_owner() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::set_auction_manager(Origin::signed(2), banner_id, Some(2)), "You do not own this banner");
+            assert_noop!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None), "You do not own this banner");
+
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, Some(2)));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionManagerSet(banner_id, Some(2))));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+            assert!(banner_events().contains(&RawEvent::StartAuction(1, banner_id, 10)));
+            assert_eq!(Banners::active_auction_count(1), 1);
+            assert_ok!(Banners::reprice_auction(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::extend_auction(Origin::signed(2), banner_id, 5));
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(Balances::free_balance(&1), 1030);
+            assert_eq!(Balances::free_balance(&2), 1000);
+
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, None));
+            assert_eq!(Banners::auction_manager(banner_id), None);
+            assert_noop!(Banners::reprice_auction(Origin::signed(2), banner_id, 40), "You do not own this banner");
+        });
+    }
+
+    #[test]
+    fn auction_manager_can_cancel_or_accept_the_highest_bid() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, Some(2)));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, Some((3, 1))));
+            assert_noop!(Banners::cancel_auction(Origin::signed(3), banner_id), "You do not own this banner");
+            assert_ok!(Banners::cancel_auction(Origin::signed(2), banner_id));
+            assert_eq!(banner_events().last(), Some(&RawEvent::CancelAuction(1, banner_id)));
+            assert!(!Banners::banner(banner_id).can_bid);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+            assert_noop!(Banners::accept_highest_bid(Origin::signed(2), banner_id), "This auction has no bids to accept");
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_noop!(Banners::cancel_auction(Origin::signed(2), banner_id), "Can't cancel an auction that has bids");
+
+            System::set_block_number(5);
+            assert_ok!(Banners::accept_highest_bid(Origin::signed(2), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Banners::last_result(banner_id), Some((3, 30, 5)));
+            assert_eq!(Balances::free_balance(&1), 1030);
+
+            // A manager whose access is revoked can't do either.
+            let other = create(3);
+            assert_ok!(Banners::set_auction_manager(Origin::signed(3), other, Some(2)));
+            assert_ok!(Banners::auction_banner(Origin::signed(3), other, 10, vec![], None, None, None));
+            assert_ok!(Banners::set_auction_manager(Origin::signed(3), other, None));
+            assert_noop!(Banners::cancel_auction(Origin::signed(2), other), "You do not own this banner");
+            assert_noop!(Banners::accept_highest_bid(Origin::signed(2), other), "You do not own this banner");
+        });
+    }
+
+    #[test]
+    fn auction_manager_is_dropped_when_the_banner_changes_hands() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, Some(2)));
+            assert_ok!(Banners::gift(Origin::signed(1), 3, banner_id, vec![]));
+            assert_eq!(Banners::auction_manager(banner_id), None);
+            assert_noop!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None), "You do not own this banner");
+        });
+    }
+
+    #[test]
+    fn reauctioning_waits_for_the_cooldown() {
+        with_externalities(&mut ExtBuilder::default().reauction_cooldown(10).build(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::auction_count(banner_id), 0);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Banners::auction_count(banner_id), 1);
+
+            System::set_block_number(AUCTION_DURATION + 5);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(Banners::last_auction_end(banner_id), Some(AUCTION_DURATION));
+
+            System::set_block_number(AUCTION_DURATION + 9);
+            assert_noop!(
+                Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None),
+                "banner in cooldown"
+            );
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5), "banner in cooldown");
+
+            System::set_block_number(AUCTION_DURATION + 10);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Banners::auction_count(banner_id), 2);
+        });
+    }
+
+    #[test]
+    fn auto_relist_is_exempt_from_the_reauction_cooldown() {
+        with_externalities(&mut ExtBuilder::default().reauction_cooldown(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 50, vec![], None, None, Some((1, 20))));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert!(Banners::banner(banner_id).can_bid);
+            assert_eq!(Banners::auction_count(banner_id), 2);
+            assert!(banner_events().contains(&RawEvent::StartAuction(1, banner_id, 30)));
+
+            // Once the relists run out, the cooldown applies as usual.
+            System::set_block_number(AUCTION_DURATION * 2);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert_noop!(
+                Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None),
+                "banner in cooldown"
+            );
+        });
+    }
+
+    #[test]
+    fn sales_add_to_fee_and_royalty_totals() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).royalty_percent(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+            assert_ok!(Banners::buy_fixed(Origin::signed(2), banner_id));
+            // The creator selling pays no royalty.
+            assert_eq!(Banners::total_fees_collected(), 10);
+            assert_eq!(Banners::total_royalties_paid(), 0);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 200));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+
+            assert_eq!(Banners::total_fees_collected(), 10 + 20);
+            assert_eq!(Banners::total_royalties_paid(), 10);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), Banners::total_fees_collected());
+        });
+    }
+
+    #[test]
+    fn accepted_offers_pay_commission_and_royalty() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).royalty_percent(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::gift(Origin::signed(1), 2, banner_id, vec![]));
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 200));
+            assert_ok!(Banners::accept_offer(Origin::signed(2), banner_id, 3));
+
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Balances::reserved_balance(&3), 0);
+            assert_eq!(Balances::free_balance(&3), 800);
+            assert_eq!(Balances::free_balance(&2), 1170);
+            assert_eq!(Balances::free_balance(&1), 1010);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 20);
+            assert_eq!(Banners::total_fees_collected(), 20);
+            assert_eq!(Banners::total_royalties_paid(), 10);
+        });
+
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).fee_payer(FeePayer::Buyer).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 200));
+            assert_ok!(Banners::accept_offer(Origin::signed(1), banner_id, 2));
+
+            // The buyer's fee comes out of their free balance on top of the offer.
+            assert_eq!(Balances::free_balance(&2), 780);
+            assert_eq!(Balances::free_balance(&1), 1200);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 20);
+            assert_eq!(Banners::total_fees_collected(), 20);
+        });
+    }
+
+    #[test]
+    fn seller_who_is_the_fee_account_keeps_the_commission() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).royalty_percent(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::gift(Origin::signed(1), FEE_ACCOUNT, banner_id, vec![]));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 200), (190, 10, 0));
+
+            assert_ok!(Banners::list_fixed_price(Origin::signed(FEE_ACCOUNT), banner_id, 200));
+            assert_ok!(Banners::buy_fixed(Origin::signed(2), banner_id));
+            assert_eq!(Balances::free_balance(&2), 800);
+            assert_eq!(Balances::free_balance(&1), 1010);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 190);
+        });
+    }
+
+    #[test]
+    fn estimate_proceeds_components_sum_to_price() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(5).royalty_percent(10).build(), || {
+            let banner_id = create(1);
+            // The creator selling their own banner pays no royalty.
+            assert_eq!(Banners::estimate_proceeds(banner_id, 1001), (951, 0, 50));
+
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            let (seller, royalty, commission) = Banners::estimate_proceeds(banner_id, 1001);
+            assert_eq!((seller, royalty, commission), (851, 100, 50));
+            assert_eq!(seller + royalty + commission, 1001);
+        });
+    }
+
+    #[test]
+    fn resale_proceeds_are_split_as_bids_arrive() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(5).royalty_percent(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 100, 5));
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 300));
+
+            assert_eq!(Balances::free_balance(&1), 1030);
+            assert_eq!(Balances::free_balance(&2), 1255);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 15);
+            assert_eq!(Balances::free_balance(&3), 1000);
+            assert_eq!(Balances::free_balance(&4), 700);
+        });
+    }
+
+    #[test]
+    fn royalties_above_the_cap_are_clamped() {
+        with_externalities(&mut ExtBuilder::default().royalty_percent(30).max_royalty_percent(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 100), (90, 10, 0));
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+            assert_eq!(Balances::free_balance(&1), 1010);
+            assert_eq!(Balances::free_balance(&2), 1090);
+            assert!(banner_events().contains(&RawEvent::RoyaltyCapped(banner_id, 10)));
+        });
+    }
+
+    #[test]
+    fn creator_royalty_can_change_until_first_sale() {
+        with_externalities(&mut ExtBuilder::default().royalty_percent(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::set_royalty(Origin::signed(1), banner_id, 20));
+            assert_eq!(banner_events().last(), Some(&RawEvent::RoyaltySet(banner_id, 20)));
+            assert_ok!(Banners::set_royalty(Origin::signed(1), banner_id, 15));
+            assert_noop!(Banners::set_royalty(Origin::signed(2), banner_id, 50), "Only the creator can set the royalty");
+            assert_noop!(Banners::set_royalty(Origin::signed(1), banner_id, 101), "Royalty can't exceed 100 percent");
+
+            assert_ok!(Banners::list_fixed_price(Origin::signed(1), banner_id, 100));
+            assert_ok!(Banners::buy_fixed(Origin::signed(2), banner_id));
+            assert!(Banners::banner(banner_id).royalty_locked);
+            assert_noop!(Banners::set_royalty(Origin::signed(1), banner_id, 50), "Royalty is locked after the first sale");
+
+            // The creator's 15% applies to resales instead of the chain-wide 10%.
+            assert_eq!(Banners::estimate_proceeds(banner_id, 100), (85, 15, 0));
+            assert_ok!(Banners::list_fixed_price(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::buy_fixed(Origin::signed(3), banner_id));
+            assert_eq!(Balances::free_balance(&1), 1115);
+            assert_eq!(Balances::free_balance(&2), 985);
+        });
+    }
+
+    #[test]
+    fn auction_deals_lock_royalty() {
+        with_externalities(&mut new_test_ext(), || {
+            let unsold = create(1);
+            let sold = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unsold, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), sold, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), sold, 20));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![unsold, sold]));
+
+            assert_ok!(Banners::set_royalty(Origin::signed(1), unsold, 5));
+            assert_noop!(Banners::set_royalty(Origin::signed(1), sold, 5), "Royalty is locked after the first sale");
+        });
+    }
+
+    #[test]
+    fn royalty_cap_is_set_by_root() {
+        with_externalities(&mut ExtBuilder::default().royalty_percent(30).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 100), (70, 30, 0));
+
+            assert_noop!(Banners::set_max_royalty_percent(Origin::signed(1), 5), "bad origin: expected to be a root origin");
+            assert_noop!(Banners::set_max_royalty_percent(system::RawOrigin::Root.into(), 101), "Royalty cap can't exceed 100 percent");
+            assert_ok!(Banners::set_max_royalty_percent(system::RawOrigin::Root.into(), 5));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 100), (95, 5, 0));
+
+            // Royalties within the cap are paid without a `RoyaltyCapped` event.
+            assert_ok!(Banners::set_max_royalty_percent(system::RawOrigin::Root.into(), 30));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+            assert!(!banner_events().iter().any(|event| match event {
+                RawEvent::RoyaltyCapped(..) => true,
+                _ => false,
+            }));
+        });
+    }
+
+    #[test]
+    fn auction_status_reports_previous_bid() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::auction_status(banner_id), None);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            let status = Banners::auction_status(banner_id).unwrap();
+            assert_eq!(status.previous_bidder, None);
+            assert_eq!(status.previous_price, 0);
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(Banners::auction_status(banner_id), Some(AuctionStatus {
+                current_price: 30,
+                current_bidder: Some(3),
+                bid_end_height: AUCTION_DURATION,
+                seconds_remaining: AUCTION_DURATION * 6,
+                previous_bidder: Some(2),
+                previous_price: 20,
+                listing: Banners::listing_metadata(banner_id),
+            }));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(1), vec![banner_id]));
+            assert_eq!(Banners::previous_bid(banner_id), None);
+        });
+    }
+
+    #[test]
+    fn auction_status_converts_blocks_remaining_to_seconds() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION - 10);
+            assert_eq!(Banners::auction_status(banner_id).unwrap().seconds_remaining, 60);
+
+            System::set_block_number(AUCTION_DURATION + 1);
+            assert_eq!(Banners::auction_status(banner_id).unwrap().seconds_remaining, 0);
+        });
+    }
+
+    #[test]
+    fn auction_status_shows_metadata_as_listed() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let listed_name = Banners::banner(banner_id).name;
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::update_metadata(Origin::signed(1), banner_id,
+                Some(b"renamed".to_vec()), Some(b"ipfs://new".to_vec()), Some(b"new desc".to_vec())));
+            assert_eq!(Banners::banner(banner_id).name, b"renamed".to_vec());
+
+            assert_eq!(Banners::auction_status(banner_id).unwrap().listing, BannerMetadata {
+                name: listed_name,
+                image_url: b"ipfs://url".to_vec(),
+                desc: b"desc".to_vec(),
+            });
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(2), vec![banner_id]));
+            assert!(!<ListingMetadata<Test>>::exists(banner_id));
+        });
+    }
+
+    #[test]
+    fn rounding_policy_decides_who_absorbs_remainders() {
+        let ext = || ExtBuilder::default().commission_percent(5).royalty_percent(10);
+
+        with_externalities(&mut ext().build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 999), (851, 99, 49));
+        });
+
+        with_externalities(&mut ext().rounding_policy(RoundingPolicy::FavorFees).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+            assert_eq!(Banners::estimate_proceeds(banner_id, 999), (849, 100, 50));
+        });
+    }
+
+    #[test]
+    fn uneven_payouts_conserve_funds() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(5).royalty_percent(10)
+            .rounding_policy(RoundingPolicy::FavorFees).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5));
+
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 333));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 999));
+
+            let royalty = Balances::free_balance(&1) - 1000;
+            let seller = Balances::free_balance(&2) - 1000;
+            let commission = Balances::free_balance(&FEE_ACCOUNT);
+            assert_eq!((seller, royalty, commission), (847, 101, 51));
+            assert_eq!(seller + royalty + commission, 999);
+            assert_eq!(Balances::free_balance(&3), 1000);
+            assert_eq!(Balances::free_balance(&4), 1);
+        });
+    }
+
+    #[test]
+    fn fee_payer_decides_who_pays_commission() {
+        let ext = || ExtBuilder::default().commission_percent(5);
+
+        with_externalities(&mut ext().build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+
+            assert_eq!(Balances::free_balance(&1), 1190);
+            assert_eq!(Balances::free_balance(&2), 1000);
+            assert_eq!(Balances::free_balance(&3), 800);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 10);
+        });
+
+        with_externalities(&mut ext().fee_payer(FeePayer::Buyer).build(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::estimate_proceeds(banner_id, 200), (200, 0, 10));
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_eq!(Balances::free_balance(&2), 895);
+
+            // The outbid bidder gets their fee back along with their bid.
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+            assert_eq!(Balances::free_balance(&1), 1200);
+            assert_eq!(Balances::free_balance(&2), 1000);
+            assert_eq!(Balances::free_balance(&3), 790);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 10);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Balances::free_balance(&3), 790);
+        });
+    }
+
+    #[test]
+    fn buyer_fee_must_be_affordable() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(5).fee_payer(FeePayer::Buyer).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 960), "Not enough balance to cover the bid");
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 950));
+            assert_eq!(Balances::free_balance(&2), 3);
+        });
+    }
+
+    #[test]
+    fn suggested_reserve_comes_from_oracle() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::suggested_reserve(banner_id), Some(100));
+            assert_eq!(Banners::suggested_reserve(H256::zero()), None);
+            assert_eq!(<() as PriceOracle<H256, u64>>::suggested_reserve(&banner_id), None);
+        });
+    }
+
+    #[test]
+    fn banners_cannot_change_hands_during_auction() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::transfer_from(1, 2, banner_id), "cannot transfer during auction");
+            assert_noop!(Banners::transfer_and_auction(Origin::signed(1), 2, banner_id, 10, 5), "This banner has already been auctioned");
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+        });
+    }
+
+    #[test]
+    fn auctions_can_be_extended_before_any_bid() {
+        with_externalities(&mut ExtBuilder::default().max_auction_duration(AUCTION_DURATION + 100).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            assert_noop!(Banners::extend_auction(Origin::signed(2), banner_id, 50), "You do not own this banner");
+            assert_ok!(Banners::extend_auction(Origin::signed(1), banner_id, 50));
+            assert_eq!(Banners::banner(banner_id).bid_end_height, AUCTION_DURATION + 50);
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionStateChanged(banner_id, AuctionState::Extended)));
+            assert!(banner_events().contains(&RawEvent::AuctionExtended(banner_id, AUCTION_DURATION + 50)));
+
+            assert_noop!(Banners::extend_auction(Origin::signed(1), banner_id, 51), "Auction duration exceeds the maximum");
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_noop!(Banners::extend_auction(Origin::signed(1), banner_id, 10), "Can't extend an auction that has bids");
+        });
+    }
+
+    #[test]
+    fn auctions_can_be_repriced_before_any_bid() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 100, vec![], None, None, None));
+
+            assert_noop!(Banners::reprice_auction(Origin::signed(2), banner_id, 50), "You do not own this banner");
+            assert_ok!(Banners::reprice_auction(Origin::signed(1), banner_id, 50));
+            assert_eq!(Banners::banner(banner_id).current_price, 50);
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionRepriced(banner_id, 50)));
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 60));
+            assert_noop!(Banners::reprice_auction(Origin::signed(1), banner_id, 10), "Can't reprice an auction that has bids");
+        });
+    }
+
+    #[test]
+    fn only_live_auctions_can_be_repriced() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_noop!(Banners::reprice_auction(Origin::signed(1), banner_id, 50), "This banner is not on auction");
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 100, vec![], None, None, None));
+            System::set_block_number(AUCTION_DURATION);
+            assert_noop!(Banners::reprice_auction(Origin::signed(1), banner_id, 50), "This auction has ended");
+        });
+    }
+
+    #[test]
+    fn bid_counts_rank_banners_and_reset_on_relist() {
+        with_externalities(&mut new_test_ext(), || {
+            let quiet = create(1);
+            let busy = create(1);
+            let unlisted = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), quiet, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), busy, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), quiet, 20));
+            assert_ok!(Banners::bid(Origin::signed(2), busy, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), busy, 30));
+            assert_noop!(Banners::bid(Origin::signed(2), busy, 30), "your bid price must be greater than current price");
+
+            assert_eq!(Banners::bid_count(busy), 2);
+            assert_eq!(Banners::bid_count(unlisted), 0);
+            assert_eq!(Banners::top_banners_by_bids(10), vec![(busy, 2), (quiet, 1)]);
+            assert_eq!(Banners::top_banners_by_bids(1), vec![(busy, 2)]);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![busy]));
+            assert_eq!(Banners::bid_count(busy), 2);
+            assert_eq!(Banners::top_banners_by_bids(10), vec![(quiet, 1)]);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(3), busy, 10, vec![], None, None, None));
+            assert_eq!(Banners::bid_count(busy), 0);
+            assert_eq!(Banners::top_banners_by_bids(10), vec![(quiet, 1)]);
+        });
+    }
+
+    #[test]
+    fn proceeds_to_a_reaped_seller_must_cover_existential_deposit() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Balances::transfer(Origin::signed(1), 4, 1000));
+            assert_eq!(Balances::total_balance(&1), 0);
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_id, 99),
+                "payment is below the existential deposit needed to recreate the recipient's account");
+            assert_eq!(Balances::free_balance(&2), 1000);
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_eq!(Balances::free_balance(&1), 100);
+        });
+    }
+
+    #[test]
+    fn bid_above_free_balance_moves_no_funds() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 50));
+            assert_ok!(Balances::transfer(Origin::signed(3), 4, 940));
+
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 100), "Not enough balance to cover the bid");
+            assert_eq!(Balances::free_balance(&3), 60);
+            assert_eq!(Balances::free_balance(&2), 950);
+            assert_eq!(Banners::banner(banner_id).current_bidder, 2);
+        });
+    }
+
+    #[test]
+    fn force_cancel_refunds_the_bidder_with_a_penalty() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).force_cancel_penalty_percent(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_noop!(Banners::force_cancel_auction(Origin::signed(1), banner_id), "This auction has no bids to refund");
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_eq!(Balances::free_balance(&1), 1090);
+            assert_noop!(Banners::force_cancel_auction(Origin::signed(2), banner_id), "You do not own this banner");
+
+            assert_ok!(Banners::force_cancel_auction(Origin::signed(1), banner_id));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AuctionForceCancelled(banner_id, 2, 5)));
+            assert_eq!(Balances::free_balance(&2), 1005);
+            assert_eq!(Balances::free_balance(&1), 985);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 10);
+
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert!(Banners::bids_of(2).is_empty());
+            assert_eq!(Banners::last_result(banner_id), None);
+            assert_eq!(Banners::active_auction_count(1), 0);
+        });
+    }
+
+    #[test]
+    fn runner_up_is_always_the_second_highest_bidder() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            let runner_up = || Banners::auction_status(banner_id).map(|status| (status.previous_bidder, status.previous_price));
+
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_eq!(runner_up(), Some((None, 0)));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            assert_eq!(runner_up(), Some((Some(2), 20)));
+
+            // The leader raising their own bid leaves the runner-up alone.
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 40));
+            assert_eq!(runner_up(), Some((Some(2), 20)));
+            assert_eq!(Balances::free_balance(&3), 960);
+
+            // The runner-up retaking the lead swaps the two.
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 50));
+            assert_eq!(runner_up(), Some((Some(3), 40)));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 60));
+            assert_eq!(runner_up(), Some((Some(2), 50)));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 70));
+            assert_eq!(runner_up(), Some((Some(2), 50)));
+            assert_eq!(Balances::free_balance(&4), 930);
+        });
+    }
+
+    #[test]
+    fn decline_win_offers_the_banner_to_the_runner_up() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(5).decline_penalty_percent(10).commission_percent(10).installments(0, 5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+            assert_noop!(Banners::decline_win(Origin::signed(3), banner_id), "This auction is not awaiting settlement");
+            assert_eq!(Balances::free_balance(&1), 1180);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 20);
+
+            System::set_block_number(AUCTION_DURATION + 1);
+            assert_noop!(Banners::decline_win(Origin::signed(2), banner_id), "Only the winning bidder can decline this banner");
+            assert_ok!(Banners::decline_win(Origin::signed(3), banner_id));
+
+            // The seller refunds the 180 they received less the 20 penalty; the commission
+            // stays with the fee account.
+            assert_eq!(banner_events().last(), Some(&RawEvent::WinDeclined(banner_id, 3, Some(2), 20)));
+            assert_eq!(Balances::free_balance(&3), 960);
+            assert_eq!(Balances::free_balance(&1), 1020);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 20);
+            assert!(Banners::bids_of(3).is_empty());
+
+            // The runner-up isn't charged until they accept.
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_eq!(Balances::free_balance(&2), 1000);
+            assert_eq!(Banners::awaiting_payment(banner_id), Some(PendingPayment {
+                winner: 2,
+                price: 100,
+                deposit: 0,
+                deadline: AUCTION_DURATION + 6,
+                runner_up: None,
+            }));
+
+            assert_ok!(Banners::finalize_payment(Origin::signed(2), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Banners::last_sale_price(banner_id), Some(100));
+            assert_eq!(Balances::free_balance(&2), 900);
+            assert_eq!(Balances::free_balance(&1), 1110);
+            assert_eq!(Balances::free_balance(&FEE_ACCOUNT), 30);
+        });
+    }
+
+    #[test]
+    fn runner_up_can_ignore_a_declined_banner() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(5).decline_penalty_percent(10).installments(0, 5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+
+            System::set_block_number(AUCTION_DURATION + 1);
+            assert_ok!(Banners::decline_win(Origin::signed(3), banner_id));
+
+            System::set_block_number(AUCTION_DURATION + 7);
+            assert_ok!(Banners::forfeit_payment(Origin::signed(4), banner_id));
+            assert_eq!(Banners::awaiting_payment(banner_id), None);
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_eq!(Balances::free_balance(&2), 1000);
+            assert_eq!(Balances::free_balance(&3), 980);
+            assert_eq!(Balances::free_balance(&1), 1020);
+        });
+    }
+
+    #[test]
+    fn decline_win_without_a_runner_up_returns_the_banner() {
+        with_externalities(&mut ExtBuilder::default().settlement_grace_period(5).decline_penalty_percent(10).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, Some((3, 5))));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+
+            System::set_block_number(AUCTION_DURATION + 1);
+            assert_ok!(Banners::decline_win(Origin::signed(3), banner_id));
+
+            assert_eq!(banner_events().last(), Some(&RawEvent::WinDeclined(banner_id, 3, None, 20)));
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            // Declining doesn't trigger the auto-relist meant for auctions nobody bid on.
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert_eq!(Banners::auto_relist(banner_id), None);
+            assert_eq!(Banners::awaiting_payment(banner_id), None);
+            assert_eq!(Balances::free_balance(&3), 980);
+            assert_eq!(Balances::free_balance(&1), 1020);
+
+            System::set_block_number(AUCTION_DURATION + 6);
+            assert_noop!(Banners::decline_win(Origin::signed(3), banner_id), "This banner is not on auction");
+        });
+    }
+
+    #[test]
+    fn blocked_accounts_are_rejected_but_their_auctions_settle() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let other = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+
+            assert_noop!(Banners::set_blocked(Origin::signed(1), 2, true), "bad origin: expected to be a root origin");
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 2, true));
+            assert_eq!(banner_events().last(), Some(&RawEvent::AccountBlocked(2, true)));
+
+            assert_noop!(Banners::create_banner(Origin::signed(2), b"blocked".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()), "account blocked");
+            assert_noop!(Banners::gift(Origin::signed(1), 2, other, vec![]), "account blocked");
+            assert_ok!(Banners::auction_banner(Origin::signed(1), other, 10, vec![], None, None, None));
+            assert_noop!(Banners::bid(Origin::signed(2), other, 20), "account blocked");
+
+            // The auction they were already leading still goes through.
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_noop!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None), "account blocked");
+
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 2, false));
+            assert!(!Banners::is_blocked(2));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+        });
+    }
+
+    #[test]
+    fn blocked_accounts_cannot_trade_outside_auctions() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let owned = create(2);
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::make_offer(Origin::signed(3), owned, 100));
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 2, true));
+
+            assert_noop!(Banners::accept_offer(Origin::signed(1), banner_id, 2), "account blocked");
+            assert_noop!(Banners::accept_offer(Origin::signed(2), owned, 3), "account blocked");
+            assert_noop!(Banners::list_fixed_price(Origin::signed(2), owned, 50), "account blocked");
+            assert_noop!(<Banners as Nft<u64>>::transfer(&1, &2, &banner_id), "account blocked");
+            assert_noop!(<Banners as Nft<u64>>::transfer(&2, &1, &owned), "account blocked");
+
+            // Withdrawing is still allowed, so blocked funds aren't stuck.
+            assert_ok!(Banners::withdraw_offer(Origin::signed(2), banner_id));
+        });
+    }
+
+    #[test]
+    fn managers_cannot_auction_for_a_blocked_owner() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let other = create(1);
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, Some(2)));
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 1, true));
+
+            assert_noop!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None), "account blocked");
+            assert_noop!(Banners::set_auction_manager(Origin::signed(1), other, Some(2)), "account blocked");
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, None));
+
+            assert_ok!(Banners::set_blocked(system::RawOrigin::Root.into(), 1, false));
+            assert_ok!(Banners::set_auction_manager(Origin::signed(1), banner_id, Some(2)));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+        });
+    }
+
+    #[test]
+    fn settle_auction_reports_a_sale() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_eq!(Banners::settle_auction(banner_id), Err("This auction can't be settled yet"));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_eq!(Banners::settle_auction(banner_id), Ok(AuctionOutcome::Sold { winner: 2, price: 20 }));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Banners::settle_auction(banner_id), Err("This banner is not on auction"));
+        });
+    }
+
+    #[test]
+    fn settle_auction_reports_an_abort() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_eq!(Banners::settle_auction(banner_id), Ok(AuctionOutcome::Aborted));
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_eq!(Banners::settle_auction(H256::zero()), Err("This banner does not exist"));
+        });
+    }
+
+    #[test]
+    fn installment_winner_pays_the_rest_to_take_the_banner() {
+        with_externalities(&mut ExtBuilder::default().installments(10, 5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_eq!(Balances::reserved_balance(&2), 10);
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+            assert_eq!(Balances::reserved_balance(&2), 0);
+            assert_eq!(Balances::free_balance(&3), 980);
+            assert_eq!(Banners::reserved_of(3), 20);
+            assert_eq!(Balances::free_balance(&1), 1000);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::claim(Origin::signed(3), banner_id));
+            assert_eq!(banner_events().last(), Some(&RawEvent::PaymentDue(banner_id, 3, 180, AUCTION_DURATION + 5)));
+            assert_eq!(Banners::awaiting_payment(banner_id), Some(PendingPayment {
+                winner: 3,
+                price: 200,
+                deposit: 20,
+                deadline: AUCTION_DURATION + 5,
+                runner_up: Some((2, 100)),
+            }));
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_noop!(Banners::gift(Origin::signed(1), 4, banner_id, vec![]), "This banner is awaiting payment");
+            assert_noop!(Banners::forfeit_payment(Origin::signed(4), banner_id), "The payment window is still open");
+
+            assert_noop!(Banners::finalize_payment(Origin::signed(2), banner_id), "Only the winning bidder can pay for this banner");
+            assert_ok!(Banners::finalize_payment(Origin::signed(3), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Banners::last_sale_price(banner_id), Some(200));
+            assert_eq!(Banners::awaiting_payment(banner_id), None);
+            assert_eq!(Balances::free_balance(&3), 800);
+            assert_eq!(Balances::reserved_balance(&3), 0);
+            assert_eq!(Banners::reserved_of(3), 0);
+            assert_eq!(Balances::free_balance(&1), 1200);
+        });
+    }
+
+    #[test]
+    fn unpaid_installment_forfeits_the_deposit_and_promotes_the_runner_up() {
+        with_externalities(&mut ExtBuilder::default().installments(10, 5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 200));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+
+            System::set_block_number(AUCTION_DURATION + 6);
+            assert_noop!(Banners::finalize_payment(Origin::signed(3), banner_id), "The payment window has closed");
+            assert_ok!(Banners::forfeit_payment(Origin::signed(4), banner_id));
+            assert_eq!(Balances::free_balance(&3), 980);
+            assert_eq!(Balances::reserved_balance(&3), 0);
+            assert_eq!(Balances::free_balance(&1), 1020);
+            let events = banner_events();
+            assert_eq!(events[events.len() - 2..], [
+                RawEvent::PaymentForfeited(banner_id, 3, 20),
+                RawEvent::PaymentDue(banner_id, 2, 100, AUCTION_DURATION + 11),
+            ]);
+
+            assert_ok!(Banners::finalize_payment(Origin::signed(2), banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_eq!(Balances::free_balance(&2), 900);
+            assert_eq!(Balances::free_balance(&1), 1120);
+        });
+    }
+
+    #[test]
+    fn bids_of_tracks_leading_bids() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            let second = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), first, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), second, 10, vec![], None, None, None));
+
+            assert_ok!(Banners::bid(Origin::signed(2), first, 20));
+            assert_ok!(Banners::bid(Origin::signed(2), second, 20));
+            assert_eq!(Banners::bids_of(2), vec![first, second]);
+
+            assert_ok!(Banners::bid(Origin::signed(3), first, 30));
+            assert_eq!(Banners::bids_of(2), vec![second]);
+            assert_eq!(Banners::bids_of(3), vec![first]);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![first, second]));
+            assert!(Banners::bids_of(2).is_empty());
+            assert!(Banners::bids_of(3).is_empty());
+        });
+    }
+
+    #[test]
+    fn leading_bids_are_capped_per_account() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_ids: Vec<H256> = (0..MAX_LEADING_BIDS + 1).map(|_| create(1)).collect();
+            for &banner_id in &banner_ids {
+                assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 1, vec![], None, None, None));
+            }
+            for &banner_id in &banner_ids[..MAX_LEADING_BIDS] {
+                assert_ok!(Banners::bid(Origin::signed(2), banner_id, 2));
+            }
+
+            assert_noop!(Banners::bid(Origin::signed(2), banner_ids[MAX_LEADING_BIDS], 2), "You are already the high bidder on too many auctions");
+            assert_ok!(Banners::bid(Origin::signed(3), banner_ids[0], 3));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_ids[MAX_LEADING_BIDS], 2));
+        });
+    }
+
+    #[test]
+    fn settling_through_bid_only_releases_deposits() {
+        with_externalities(&mut ExtBuilder::default().commission_percent(10).royalty_percent(5).listing_deposit(5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::gift(Origin::signed(1), 2, banner_id, vec![]));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+
+            let balances: Vec<u64> = ACCOUNTS.iter().map(|who| Balances::total_balance(who)).collect();
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 200));
+
+            // Ownership and the auction flags change; every payout already happened at bid time.
+            // The only currency call left is handing the seller's listing deposit back.
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert!(!Banners::banner(banner_id).can_bid);
+            assert_eq!(ACCOUNTS.iter().map(|who| Balances::total_balance(who)).collect::<Vec<_>>(), balances);
+            assert_eq!(Balances::reserved_balance(&2), 0);
+            assert_eq!(Banners::reserved_of(2), 0);
+        });
+    }
+
+    #[test]
+    fn currency_hooks_find_the_sale_already_complete() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(1).installments(10, 5).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::gift(Origin::signed(1), 2, banner_id, vec![]));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 100));
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::claim(Origin::signed(3), banner_id));
+
+            // With the seller's account reaped, paying them runs the probe mid-payment.
+            assert_ok!(Balances::transfer(Origin::signed(2), 4, 1000));
+            assert_eq!(Balances::total_balance(&2), 0);
+            PROBED_BANNER.with(|probed| *probed.borrow_mut() = Some(banner_id));
+            assert_ok!(Banners::finalize_payment(Origin::signed(3), banner_id));
+
+            let probes = PROBES.with(|probes| probes.borrow().clone());
+            assert_eq!(probes, vec![(Some(3), false, Err("This banner is not awaiting payment"))]);
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_eq!(Balances::free_balance(&2), 100);
+            assert_eq!(Balances::free_balance(&3), 900);
+        });
+    }
+
+    #[test]
+    fn cancel_all_auctions_only_cancels_unbid_auctions() {
+        with_externalities(&mut new_test_ext(), || {
+            let unbid = create(1);
+            let bid_on = create(1);
+            let idle = create(1);
+            let relisting = create(1);
+            let others = create(2);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), unbid, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), bid_on, 10, vec![], None, None, None));
+            assert_ok!(Banners::auction_banner(Origin::signed(1), relisting, 10, vec![], None, None, Some((3, 1))));
+            assert_ok!(Banners::auction_banner(Origin::signed(2), others, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(3), bid_on, 20));
+
+            System::set_block_number(5);
+            assert_ok!(Banners::cancel_all_auctions(Origin::signed(1), 0));
+
+            let events = banner_events();
+            assert!(events.contains(&RawEvent::CancelAuction(1, unbid)));
+            assert!(events.contains(&RawEvent::CancelAuction(1, relisting)));
+            assert!(!events.contains(&RawEvent::CancelAuction(1, bid_on)));
+            assert!(!events.contains(&RawEvent::CancelAuction(1, idle)));
+            assert!(!Banners::banner(unbid).can_bid);
+            assert!(!Banners::banner(relisting).can_bid);
+            assert!(Banners::banner(bid_on).can_bid);
+            assert!(Banners::banner(others).can_bid);
+            assert_eq!(Banners::active_auction_count(1), 1);
+            assert_eq!(Banners::last_auction_end(unbid), Some(5));
+            assert_ok!(Banners::do_try_state());
+        });
+    }
+
+    #[test]
+    fn cancel_all_auctions_scans_a_bounded_batch() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_ids: Vec<_> = (0..MAX_CANCEL_BATCH + 2).map(|_| create(1)).collect();
+            for &banner_id in banner_ids.iter() {
+                assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            }
+
+            assert_ok!(Banners::cancel_all_auctions(Origin::signed(1), 0));
+            assert_eq!(Banners::active_auction_count(1), 2);
+            assert!(Banners::banner(banner_ids[MAX_CANCEL_BATCH as usize]).can_bid);
+
+            assert_ok!(Banners::cancel_all_auctions(Origin::signed(1), MAX_CANCEL_BATCH));
+            assert_eq!(Banners::active_auction_count(1), 0);
+            // Past the end there is nothing left to look at.
+            assert_ok!(Banners::cancel_all_auctions(Origin::signed(1), MAX_CANCEL_BATCH + 2));
+        });
+    }
+
+    #[test]
+    fn last_result_records_the_final_winner_and_price() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            assert_eq!(Banners::last_result(banner_id), None);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(3), banner_id, 30));
+            System::set_block_number(AUCTION_DURATION + 2);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+            assert_eq!(Banners::last_result(banner_id), Some((3, 30, AUCTION_DURATION)));
+
+            // A new auction replaces the old result; this one ends without a sale.
+            assert_ok!(Banners::auction_banner(Origin::signed(3), banner_id, 10, vec![], None, None, None));
+            assert_eq!(Banners::last_result(banner_id), None);
+            System::set_block_number(AUCTION_DURATION * 3);
+            assert_ok!(Banners::settle_expired(Origin::signed(4), vec![banner_id]));
+            assert_eq!(Banners::last_result(banner_id), None);
+        });
+    }
+
+    #[test]
+    fn root_reclaims_banners_of_reaped_owners() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).build(), || {
+            let orphaned = create(1);
+            let auctioned = create(1);
+            assert_noop!(Banners::reclaim_orphaned(system::RawOrigin::Root.into(), orphaned), "The owner's account still exists");
+
+            assert_ok!(Balances::transfer(Origin::signed(1), 4, 1000));
+            assert_eq!(Balances::total_balance(&1), 0);
+            assert_eq!(Banners::owner_of(orphaned), Some(1));
+
+            // The ownership record outlives the account, so the owner can still auction.
+            assert_ok!(Banners::auction_banner(Origin::signed(1), auctioned, 10, vec![], None, None, None));
+            assert_noop!(Banners::reclaim_orphaned(system::RawOrigin::Root.into(), auctioned), "cannot transfer during auction");
+
+            assert_noop!(Banners::reclaim_orphaned(Origin::signed(2), orphaned), "bad origin: expected to be a root origin");
+            assert_ok!(Banners::reclaim_orphaned(system::RawOrigin::Root.into(), orphaned));
+            assert_eq!(Banners::owner_of(orphaned), Some(REWARD_POOL));
+            assert_eq!(banner_events().last(), Some(&RawEvent::Reclaimed(1, orphaned)));
+            assert_ok!(Banners::do_try_state());
+        });
+    }
+
+    #[test]
+    fn refund_to_a_reaped_bidder_must_cover_existential_deposit() {
+        with_externalities(&mut ExtBuilder::default().existential_deposit(100).build(), || {
+            let banner_id = create(1);
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 50));
+            assert_ok!(Balances::transfer(Origin::signed(2), 4, 950));
+            assert_eq!(Balances::total_balance(&2), 0);
+
+            assert_noop!(Banners::bid(Origin::signed(3), banner_id, 60),
+                "payment is below the existential deposit needed to recreate the recipient's account");
+            assert_eq!(Balances::free_balance(&1), 1050);
+            assert_eq!(Balances::free_balance(&3), 1000);
+        });
     }
-}
\ No newline at end of file
+
+    /// Stand-in for a module that takes NFTs as collateral, written only against `Nft`.
+    struct Vault<N>(rstd::marker::PhantomData<N>);
+
+    const VAULT: u64 = 99;
+
+    impl<N: Nft<u64>> Vault<N> {
+        fn deposit(who: u64, id: N::Id) -> Result {
+            ensure!(N::exists(&id), "unknown token");
+            N::transfer(&who, &VAULT, &id)
+        }
+
+        fn withdraw(who: u64, id: N::Id) -> Result {
+            ensure!(N::owner(&id) == Some(VAULT), "token is not in the vault");
+            N::transfer(&VAULT, &who, &id)
+        }
+    }
+
+    #[test]
+    fn banners_work_through_the_generic_nft_interface() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+
+            assert_noop!(Vault::<Banners>::deposit(2, banner_id), "'from' account does not own this banner");
+            assert_ok!(Vault::<Banners>::deposit(1, banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(VAULT));
+            assert_eq!(Banners::owned_banner_count(VAULT), 1);
+
+            assert_ok!(Vault::<Banners>::withdraw(1, banner_id));
+            assert_eq!(Banners::owner_of(banner_id), Some(1));
+            assert_noop!(Vault::<Banners>::withdraw(1, banner_id), "token is not in the vault");
+
+            assert_noop!(<Banners as Nft<u64>>::transfer(&1, &1, &banner_id), "'from' and 'to' are the same account");
+            assert!(!<Banners as Nft<u64>>::exists(&H256::zero()));
+        });
+    }
+
+    /// Every account the tests move funds between.
+    const ACCOUNTS: [u64; 5] = [1, 2, 3, 4, FEE_ACCOUNT];
+
+    /// Asserts that each account's reserved balance is exactly the sum of its outstanding offers,
+    /// listing deposits and installment deposits on `banner_ids`, and that no funds have been
+    /// created or destroyed.
+    fn assert_reserves_balanced(banner_ids: &[H256]) {
+        for &who in ACCOUNTS.iter() {
+            let offered: u64 = banner_ids.iter().map(|&banner_id| Banners::offer((banner_id, who))).sum();
+            let deposited: u64 = banner_ids.iter()
+                .filter_map(|&banner_id| Banners::held_listing_deposit(banner_id))
+                .filter(|&(depositor, _)| depositor == who)
+                .map(|(_, deposit)| deposit)
+                .sum();
+            let escrowed: u64 = banner_ids.iter()
+                .filter_map(|&banner_id| Banners::bid_escrow(banner_id)
+                    .or_else(|| Banners::awaiting_payment(banner_id).map(|pending| (pending.winner, pending.deposit))))
+                .filter(|&(holder, _)| holder == who)
+                .map(|(_, deposit)| deposit)
+                .sum();
+            let held = offered + deposited + escrowed;
+            assert_eq!(Balances::reserved_balance(&who), held, "reserved balance of {} drifted from its offers and deposits", who);
+            assert_eq!(Banners::reserved_of(who), held, "reserved_of({}) drifted from its offers and deposits", who);
+        }
+
+        let total: u64 = ACCOUNTS.iter().map(|who| Balances::total_balance(who)).sum();
+        assert_eq!(total, Balances::total_issuance());
+        assert_ok!(Banners::do_try_state());
+    }
+
+    #[test]
+    fn reserves_match_offers_through_auction_and_offer_lifecycle() {
+        with_externalities(&mut new_test_ext(), || {
+            let banner_id = create(1);
+            let banner_ids = [banner_id];
+            assert_reserves_balanced(&banner_ids);
+
+            assert_ok!(Banners::make_offer(Origin::signed(2), banner_id, 100));
+            assert_ok!(Banners::make_offer(Origin::signed(3), banner_id, 50));
+            assert_reserves_balanced(&banner_ids);
+
+            assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, 10, vec![], None, None, None));
+            assert_ok!(Banners::bid(Origin::signed(4), banner_id, 20));
+            assert_ok!(Banners::bid(Origin::signed(2), banner_id, 30));
+            assert_reserves_balanced(&banner_ids);
+
+            System::set_block_number(AUCTION_DURATION);
+            assert_ok!(Banners::settle_expired(Origin::signed(3), vec![banner_id]));
+            assert_eq!(Banners::owner_of(banner_id), Some(2));
+            assert_reserves_balanced(&banner_ids);
+
+            // The auction winner still has an offer outstanding, which they can't accept themselves.
+            assert_noop!(Banners::accept_offer(Origin::signed(2), banner_id, 2), "You can't accept your own offer");
+
+            assert_ok!(Banners::accept_offer(Origin::signed(2), banner_id, 3));
+            assert_eq!(Banners::owner_of(banner_id), Some(3));
+            assert_reserves_balanced(&banner_ids);
+
+            assert_ok!(Banners::withdraw_offer(Origin::signed(2), banner_id));
+            assert_reserves_balanced(&banner_ids);
+            for &who in ACCOUNTS.iter() {
+                assert_eq!(Balances::reserved_balance(&who), 0);
+            }
+        });
+    }
+
+    #[test]
+    fn random_operations_keep_reserves_balanced() {
+        with_externalities(&mut new_test_ext(), run_random_operations);
+        with_externalities(&mut ExtBuilder::default().listing_deposit(5).build(), run_random_operations);
+        with_externalities(&mut ExtBuilder::default().listing_deposit(5).installments(20, AUCTION_DURATION / 2).build(), run_random_operations);
+    }
+
+    fn run_random_operations() {
+        let banner_ids = vec![create(1), create(2), create(3)];
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+
+        for _ in 0..500 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+
+            let who = seed % 4 + 1;
+            let other = (seed >> 8) % 4 + 1;
+            let banner_id = banner_ids[(seed >> 16) as usize % banner_ids.len()];
+            let amount = (seed >> 24) % 100 + 1;
+
+            // Failed calls aren't rolled back, so they must leave reserves consistent too.
+            let _ = match (seed >> 40) % 8 {
+                0 => Banners::make_offer(Origin::signed(who), banner_id, amount),
+                1 => Banners::withdraw_offer(Origin::signed(who), banner_id),
+                2 => Banners::accept_offer(Origin::signed(who), banner_id, other),
+                3 => Banners::auction_banner(Origin::signed(who), banner_id, amount, vec![], None, None, None),
+                4 => Banners::bid(Origin::signed(who), banner_id, Banners::banner(banner_id).current_price + amount),
+                5 => Banners::finalize_payment(Origin::signed(who), banner_id),
+                6 => Banners::forfeit_payment(Origin::signed(who), banner_id),
+                _ => {
+                    System::set_block_number(System::block_number() + AUCTION_DURATION / 4);
+                    Banners::settle_expired(Origin::signed(who), banner_ids.clone())
+                }
+            };
+
+            assert_reserves_balanced(&banner_ids);
+        }
+    }
+
+    #[test]
+    fn reserved_of_tracks_offers_across_banners() {
+        with_externalities(&mut new_test_ext(), || {
+            let first = create(1);
+            let second = create(1);
+            let third = create(3);
+
+            assert_ok!(Banners::make_offer(Origin::signed(2), first, 100));
+            assert_ok!(Banners::make_offer(Origin::signed(2), second, 50));
+            assert_ok!(Banners::make_offer(Origin::signed(2), third, 25));
+            assert_ok!(Banners::make_offer(Origin::signed(4), first, 10));
+            assert_eq!(Banners::reserved_of(2), 175);
+            assert_eq!(Banners::reserved_of(4), 10);
+
+            assert_ok!(Banners::accept_offer(Origin::signed(1), first, 2));
+            assert_eq!(Banners::reserved_of(2), 75);
+
+            assert_ok!(Banners::withdraw_offer(Origin::signed(2), third));
+            assert_eq!(Banners::reserved_of(2), 50);
+            assert_eq!(Banners::reserved_of(2), Balances::reserved_balance(&2));
+            assert_eq!(Banners::reserved_of(1), 0);
+        });
+    }
+
+    /// A second runtime whose balances don't fit in a `u64`, to catch conversions that assume
+    /// they do.
+    mod wide_balance {
+        use super::*;
+
+        impl_outer_origin! {
+            pub enum Origin for WideTest {}
+        }
+
+        mod banners {
+            pub use super::super::super::Event;
+        }
+
+        impl_outer_event! {
+            pub enum WideEvent for WideTest {
+                balances<T>, banners<T>,
+            }
+        }
+
+        #[derive(Clone, Eq, PartialEq)]
+        pub struct WideTest;
+        impl system::Trait for WideTest {
+            type Origin = Origin;
+            type Index = u64;
+            type BlockNumber = u64;
+            type Hash = H256;
+            type Hashing = BlakeTwo256;
+            type Digest = Digest;
+            type AccountId = u64;
+            type Lookup = IdentityLookup<Self::AccountId>;
+            type Header = Header;
+            type Event = WideEvent;
+            type Log = DigestItem;
+        }
+        impl balances::Trait for WideTest {
+            type Balance = u128;
+            type OnFreeBalanceZero = ();
+            type OnNewAccount = ();
+            type Event = WideEvent;
+            type TransactionPayment = ();
+            type TransferPayment = ();
+            type DustRemoval = ();
+        }
+        impl Trait for WideTest {
+            type Event = WideEvent;
+            type PriceOracle = ();
+            type AttestationCheck = ();
+            type OnAuctionSettled = ();
+        }
+        type System = system::Module<WideTest>;
+        type Balances = balances::Module<WideTest>;
+        type Banners = Module<WideTest>;
+
+        /// Well past `u64::MAX` and a multiple of 100, so the commission is exact.
+        const PRICE: u128 = 100 << 64;
+
+        fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+            let mut t = system::GenesisConfig::<WideTest>::default().build_storage().unwrap().0;
+            t.extend(balances::GenesisConfig::<WideTest> {
+                balances: vec![(1, 1000), (2, 2 * PRICE)],
+                transaction_base_fee: 0,
+                transaction_byte_fee: 0,
+                existential_deposit: 0,
+                transfer_fee: 0,
+                creation_fee: 0,
+                vesting: vec![],
+            }.build_storage().unwrap().0);
+            t.extend(GenesisConfig::<WideTest> {
+                fee_account: FEE_ACCOUNT,
+                commission_percent: 10,
+                max_royalty_percent: 100,
+                allowed_url_schemes: vec![b"ipfs://".to_vec()],
+                ..Default::default()
+            }.build_storage().unwrap().0);
+            t.into()
+        }
+
+        #[test]
+        fn prices_above_u64_max_settle_exactly() {
+            with_externalities(&mut new_test_ext(), || {
+                assert_ok!(Banners::create_banner(Origin::signed(1), b"wide".to_vec(), b"ipfs://url".to_vec(), b"desc".to_vec(), H256::zero()));
+                let banner_id = Banners::banner_of_owner_by_index((1, 0));
+
+                assert_ok!(Banners::auction_banner(Origin::signed(1), banner_id, u64::max_value() as u128, vec![], None, None, None));
+                assert_ok!(Banners::bid(Origin::signed(2), banner_id, PRICE));
+                assert_eq!(Banners::banner(banner_id).current_price, PRICE);
+
+                System::set_block_number(AUCTION_DURATION + 2);
+                assert_ok!(Banners::claim(Origin::signed(2), banner_id));
+
+                assert_eq!(Banners::owner_of(banner_id), Some(2));
+                assert_eq!(Banners::last_sale_price(banner_id), Some(PRICE));
+                assert_eq!(Balances::free_balance(&FEE_ACCOUNT), PRICE / 10);
+                assert_eq!(Balances::free_balance(&1), 1000 + PRICE - PRICE / 10);
+                assert_eq!(Balances::free_balance(&2), PRICE);
+            });
+        }
+    }
+}
@@ -33,6 +33,7 @@ pub use balances::Call as BalancesCall;
 pub use runtime_primitives::{Permill, Perbill};
 pub use timestamp::BlockPeriod;
 pub use support::{StorageValue, construct_runtime};
+pub use banners::{Banner, BannerDetail, BannerMetadata, AuctionStatus, AuctionView, BannersApi, FeePayer, RoundingPolicy};
 
 /// The type that is used for identifying authorities.
 pub type AuthorityId = <AuthoritySignature as Verify>::Signer;
@@ -55,6 +56,9 @@ pub type BlockNumber = u64;
 /// Index of an account's extrinsic in the chain.
 pub type Nonce = u64;
 
+/// Balance of an account.
+pub type Balance = u128;
+
 /// Used for the module template in `./template.rs`
 mod template;
 
@@ -170,7 +174,7 @@ impl timestamp::Trait for Runtime {
 
 impl balances::Trait for Runtime {
 	/// The type for recording an account's balance.
-	type Balance = u128;
+	type Balance = Balance;
 	/// What to do if an account's free balance gets zeroed.
 	type OnFreeBalanceZero = ();
 	/// What to do if a new account is created.
@@ -196,6 +200,12 @@ impl template::Trait for Runtime {
 
 impl banners::Trait for Runtime {
 	type Event = Event;
+	/// No price oracle is wired in yet.
+	type PriceOracle = ();
+	/// Every account may place high-value bids.
+	type AttestationCheck = ();
+	/// No other module reacts to settlements yet.
+	type OnAuctionSettled = ();
 }
 
 construct_runtime!(
@@ -213,7 +223,7 @@ construct_runtime!(
 		Sudo: sudo,
 		// Used for the module template in `./template.rs`
 		TemplateModule: template::{Module, Call, Storage, Event<T>},
-		Banners: banners::{Module, Call, Storage, Event<T>},
+		Banners: banners::{Module, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -305,4 +315,90 @@ impl_runtime_apis! {
 			Consensus::authorities()
 		}
 	}
+
+	impl banners::BannersApi<Block, AccountId, Hash, Balance, BlockNumber> for Runtime {
+		fn auctions_ending_for(who: AccountId) -> Vec<(Hash, BlockNumber)> {
+			Banners::auctions_ending_for(who)
+		}
+
+		fn is_name_available(name: Vec<u8>) -> bool {
+			Banners::is_name_available(name)
+		}
+
+		fn collection_stats() -> (u64, u64) {
+			Banners::collection_stats()
+		}
+
+		fn owned_banner_at(owner: AccountId, index: u64) -> Option<Banner<Hash, Balance, AccountId, BlockNumber>> {
+			Banners::owned_banner_at(owner, index)
+		}
+
+		fn current_bidder_of(banner_id: Hash) -> Option<AccountId> {
+			Banners::current_bidder_of(banner_id)
+		}
+
+		fn estimate_proceeds(banner_id: Hash, sale_price: Balance) -> (Balance, Balance, Balance) {
+			Banners::estimate_proceeds(banner_id, sale_price)
+		}
+
+		fn auction_status(banner_id: Hash) -> Option<AuctionStatus<AccountId, Balance, BlockNumber>> {
+			Banners::auction_status(banner_id)
+		}
+
+		fn suggested_reserve(banner_id: Hash) -> Option<Balance> {
+			Banners::suggested_reserve(banner_id)
+		}
+
+		fn top_banners_by_bids(limit: u32) -> Vec<(Hash, u32)> {
+			Banners::top_banners_by_bids(limit)
+		}
+
+		fn provenance(banner_id: Hash) -> Vec<(AccountId, BlockNumber)> {
+			Banners::provenance(banner_id)
+		}
+
+		fn reserved_of(who: AccountId) -> Balance {
+			Banners::reserved_of(who)
+		}
+
+		fn bids_of(who: AccountId) -> Vec<Hash> {
+			Banners::bids_of(who)
+		}
+
+		fn active_auctions_detailed(start: u64, limit: u64) -> Vec<AuctionView<Hash, AccountId, Balance, BlockNumber>> {
+			Banners::active_auctions_detailed(start, limit)
+		}
+
+		fn banner_detail(banner_id: Hash) -> Option<BannerDetail<Hash, Balance, AccountId, BlockNumber>> {
+			Banners::banner_detail(banner_id)
+		}
+
+		fn validate_metadata(name: Vec<u8>, url: Vec<u8>, desc: Vec<u8>) -> rstd::result::Result<(), Vec<u8>> {
+			Banners::validate_metadata(&name, &url, &desc).map_err(|reason| reason.as_bytes().to_vec())
+		}
+
+		fn banners_by_ids(ids: Vec<Hash>) -> Vec<(Hash, Option<Banner<Hash, Balance, AccountId, BlockNumber>>)> {
+			Banners::banners_by_ids(ids)
+		}
+
+		fn localized_metadata(banner_id: Hash, lang: [u8; 2]) -> (Vec<u8>, Vec<u8>) {
+			Banners::localized_metadata(banner_id, lang)
+		}
+
+		fn banners_page(cursor: Option<u64>, limit: u64) -> (Vec<Hash>, Option<u64>) {
+			Banners::banners_page(cursor, limit)
+		}
+
+		fn banners_of(owner: AccountId, cursor: Option<u64>, limit: u64) -> (Vec<Hash>, Option<u64>) {
+			Banners::banners_of(owner, cursor, limit)
+		}
+
+		fn supply_at(block: BlockNumber) -> Option<u64> {
+			Banners::supply_at(block)
+		}
+
+		fn banners_with_image(url: Vec<u8>) -> Vec<Hash> {
+			Banners::banners_with_image(url)
+		}
+	}
 }
@@ -1,7 +1,7 @@
 use primitives::{ed25519, sr25519, Pair};
 use apollo_runtime::{
 	AccountId, GenesisConfig, ConsensusConfig, TimestampConfig, BalancesConfig,
-	SudoConfig, IndicesConfig,
+	SudoConfig, IndicesConfig, BannersConfig, FeePayer, RoundingPolicy,
 };
 use substrate_service;
 
@@ -113,7 +113,39 @@ fn testnet_genesis(initial_authorities: Vec<AuthorityId>, endowed_accounts: Vec<
 			vesting: vec![],
 		}),
 		sudo: Some(SudoConfig {
-			key: root_key,
+			key: root_key.clone(),
+		}),
+		banners: Some(BannersConfig {
+			require_live_bidders: true,
+			reward_pool: root_key.clone(),
+			bid_deposit: 0,
+			listing_deposit: 0,
+			installment_deposit_percent: 0,
+			payment_window: 100,
+			fee_account: root_key,
+			burn_address: AccountId::default(),
+			high_value_threshold: 0,
+			commission_percent: 0,
+			force_cancel_penalty_percent: 10,
+			decline_penalty_percent: 10,
+			royalty_percent: 0,
+			max_royalty_percent: 100,
+			rounding_policy: RoundingPolicy::FavorSeller,
+			fee_payer: FeePayer::Seller,
+			max_auction_duration: 0,
+			min_auction_duration: 10,
+			bid_cooldown: 0,
+			reauction_cooldown: 0,
+			bid_increment_tiers: vec![(0, 1), (100, 5), (1000, 50)],
+			anti_snipe_window: 10,
+			settlement_grace_period: 100,
+			max_active_auctions_per_account: 0,
+			max_offers_per_banner: 16,
+			allowed_url_schemes: vec![b"ipfs://".to_vec(), b"https://".to_vec()],
+			case_insensitive_names: true,
+			supply_sample_interval: 8640,
+			supply_retention: 365,
+			seconds_per_block: 10, // Twice the timestamp minimum_period.
 		}),
 	}
 }